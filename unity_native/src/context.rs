@@ -0,0 +1,159 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use thiserror::Error;
+
+use crate::{GetError, UnityInterface, UnityInterfaces, VersionedInterface, lease};
+
+type CachedInterface = Arc<dyn Any + Send + Sync>;
+
+/// The process-wide context of the loaded plugin, available through [context]
+/// between `UnityPluginLoad` and `UnityPluginUnload`. Interface wrappers
+/// obtained through it are created once and shared afterwards.
+///
+/// A context kept past `UnityPluginUnload` stays bound to the unloaded plugin,
+/// and all of its accessors return [ContextError::NotLoaded].
+#[derive(Debug)]
+pub struct PluginContext {
+    interfaces: UnityInterfaces,
+    cache: Mutex<HashMap<(TypeId, bool), CachedInterface>>,
+    generation: u64,
+}
+
+/// An error while accessing the global [PluginContext]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextError {
+    /// The plugin has not been loaded by Unity yet, or was already unloaded
+    #[error("The plugin is not loaded")]
+    NotLoaded,
+}
+
+/// An error while getting an interface wrapper from the [PluginContext]
+#[derive(Error, Debug)]
+pub enum ContextGetError<T> {
+    /// The context belongs to a plugin instance that was unloaded
+    #[error(transparent)]
+    Context(#[from] ContextError),
+
+    /// The wrapper could not be constructed
+    #[error(transparent)]
+    Get(#[from] GetError<T>),
+}
+
+static CONTEXT: RwLock<Option<Arc<PluginContext>>> = RwLock::new(None);
+
+/// Returns the context of the loaded plugin. This is only available when the plugin
+/// entry points were generated by this crate, and returns an error before
+/// `UnityPluginLoad` or after `UnityPluginUnload`.
+pub fn context() -> Result<Arc<PluginContext>, ContextError> {
+    CONTEXT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or(ContextError::NotLoaded)
+}
+
+pub(crate) fn init(interfaces: &UnityInterfaces) {
+    let context = PluginContext {
+        interfaces: interfaces.clone(),
+        cache: Mutex::new(HashMap::new()),
+        generation: lease::current(),
+    };
+
+    *CONTEXT.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(context));
+}
+
+pub(crate) fn teardown() {
+    // Taken out first, so that wrappers are not dropped while holding the lock
+    let context = CONTEXT.write().unwrap_or_else(|e| e.into_inner()).take();
+    drop(context);
+}
+
+impl PluginContext {
+    /// The interfaces Unity passed to `UnityPluginLoad`
+    pub fn interfaces(&self) -> Result<&UnityInterfaces, ContextError> {
+        self.ensure_loaded()?;
+
+        Ok(&self.interfaces)
+    }
+
+    /// Returns the shared wrapper for the requested Unity API, constructing it
+    /// with [UnityInterfaces::get] on first use. Failures are not cached.
+    pub fn get<T>(&self) -> Result<Arc<T>, ContextGetError<T::FFIConversionError>>
+    where
+        T: UnityInterface + Send + Sync + 'static,
+    {
+        self.get_or_create((TypeId::of::<T>(), false), || self.interfaces.get::<T>())
+    }
+
+    /// Returns the shared wrapper for the requested Unity API, constructing it
+    /// with [UnityInterfaces::get_best] on first use. Failures are not cached.
+    pub fn get_best<T>(&self) -> Result<Arc<T>, ContextGetError<T::FFIConversionError>>
+    where
+        T: VersionedInterface + Send + Sync + 'static,
+    {
+        self.get_or_create((TypeId::of::<T>(), true), || {
+            self.interfaces.get_best::<T>()
+        })
+    }
+
+    fn ensure_loaded(&self) -> Result<(), ContextError> {
+        lease::with(self.generation, || ()).ok_or(ContextError::NotLoaded)
+    }
+
+    /// Holds the lease while creating the wrapper, so that it cannot be bound
+    /// to a plugin instance loaded after this one
+    fn get_or_create<T: Send + Sync + 'static, E>(
+        &self,
+        key: (TypeId, bool),
+        create: impl FnOnce() -> Result<T, GetError<E>>,
+    ) -> Result<Arc<T>, ContextGetError<E>> {
+        lease::with(self.generation, || {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+
+            if let Some(cached) = cache.get(&key) {
+                let cached = cached.clone();
+                return Ok(cached
+                    .downcast::<T>()
+                    .expect("Cache entries are keyed by their own type"));
+            }
+
+            let created = Arc::new(create()?);
+            cache.insert(key, created.clone());
+
+            Ok(created)
+        })
+        .unwrap_or(Err(ContextError::NotLoaded.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::UnityGraphics;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn available_between_load_and_unload() {
+        let mock = MockUnity::new();
+
+        assert_eq!(context().unwrap_err(), ContextError::NotLoaded);
+
+        crate::__private::plugin_loaded(&mock.interfaces());
+
+        let ctx = context().unwrap();
+        let first = ctx.get::<UnityGraphics>().unwrap();
+        let second = context().unwrap().get::<UnityGraphics>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        crate::__private::plugin_unloaded();
+
+        assert_eq!(context().unwrap_err(), ContextError::NotLoaded);
+        assert_eq!(ctx.interfaces().unwrap_err(), ContextError::NotLoaded);
+        assert!(matches!(
+            ctx.get::<UnityGraphics>(),
+            Err(ContextGetError::Context(ContextError::NotLoaded))
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+//! Tracks every callback this crate registers with Unity on behalf of the plugin.
+//! Unity keeps calling registered callbacks until they are unregistered, even
+//! after the plugin library was unmapped, so the generated `UnityPluginUnload`
+//! undoes all of them before running any user code.
+//!
+//! Vulkan init intercepts are not tracked, because there are no bindings for
+//! `IUnityGraphicsVulkan` to register them through yet.
+
+use std::os::raw::c_int;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Unregisters a callback, returning the error code if Unity rejected it
+type Undo = Box<dyn FnOnce() -> Result<(), c_int> + Send>;
+
+struct Tracked {
+    id: u64,
+    name: &'static str,
+    undo: Undo,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+plugin_static! {
+    fn tracked() -> &Mutex<Vec<Tracked>> = Mutex::new(Vec::new());
+}
+
+/// Identifies a tracked registration, so that it can be undone before unload
+#[derive(Debug)]
+pub(crate) struct TrackId(u64);
+
+/// Tracks a registration made with Unity, together with the function that undoes it
+pub(crate) fn track(
+    name: &'static str,
+    undo: impl FnOnce() -> Result<(), c_int> + Send + 'static,
+) -> TrackId {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    tracked()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Tracked {
+            id,
+            name,
+            undo: Box::new(undo),
+        });
+
+    TrackId(id)
+}
+
+/// Undoes a single registration ahead of unload.
+/// Returns [None] if it was already undone by [undo_all].
+pub(crate) fn undo(id: TrackId) -> Option<Result<(), c_int>> {
+    let tracked = {
+        let mut all = tracked().lock().unwrap_or_else(|e| e.into_inner());
+        let index = all.iter().position(|tracked| tracked.id == id.0)?;
+        all.remove(index)
+    };
+
+    Some((tracked.undo)())
+}
+
+/// Undoes all tracked registrations in reverse order, logging the ones Unity rejected
+pub(crate) fn undo_all() {
+    let all = std::mem::take(&mut *tracked().lock().unwrap_or_else(|e| e.into_inner()));
+
+    for tracked in all.into_iter().rev() {
+        if let Err(code) = (tracked.undo)() {
+            crate::panic::log_error(&format!(
+                "Unity rejected unregistering the {}: error code {}",
+                tracked.name, code
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn undoes_in_reverse_order() {
+        let _mock = MockUnity::new();
+        let order = std::sync::Arc::new(Mutex::new(Vec::new()));
+
+        for name in ["first", "second", "third"] {
+            let order = order.clone();
+            track(name, move || {
+                order.lock().unwrap().push(name);
+                Ok(())
+            });
+        }
+
+        let early = track("early", || Err(1));
+        assert_eq!(undo(early), Some(Err(1)));
+
+        undo_all();
+
+        assert_eq!(*order.lock().unwrap(), ["third", "second", "first"]);
+    }
+}
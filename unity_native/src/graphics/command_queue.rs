@@ -0,0 +1,199 @@
+use std::os::raw::c_int;
+use std::sync::LazyLock;
+
+use crossbeam_queue::ArrayQueue;
+use thiserror::Error;
+
+use super::{GfxDeviceEvent, RenderEventErr, RenderEventRange, RenderThread};
+use crate::panic::guard;
+
+/// A command executed on the render thread
+pub type RenderCommand = Box<dyn FnOnce(&RenderThread) + Send>;
+
+/// Returned by [RenderCommandQueue::push] when the queue is full, because the
+/// render thread did not drain it. Holds the rejected command, so it can be retried.
+#[derive(Error)]
+#[error("The render command queue is full")]
+pub struct QueueFull(pub RenderCommand);
+
+impl std::fmt::Debug for QueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("QueueFull").finish_non_exhaustive()
+    }
+}
+
+struct CommandQueue {
+    commands: ArrayQueue<RenderCommand>,
+}
+
+impl CommandQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            commands: ArrayQueue::new(capacity),
+        }
+    }
+
+    fn push(&self, command: RenderCommand) -> Result<(), QueueFull> {
+        self.commands.push(command).map_err(QueueFull)
+    }
+
+    /// Runs the commands that were queued when draining started. Commands
+    /// pushed by the commands themselves run during the next drain.
+    fn drain(&self, render_thread: &RenderThread) {
+        for _ in 0..self.commands.len() {
+            let Some(command) = self.commands.pop() else {
+                break;
+            };
+
+            guard("render command", (), || command(render_thread));
+        }
+    }
+
+    fn clear(&self) {
+        while self.commands.pop().is_some() {}
+    }
+}
+
+plugin_static! {
+    fn queue() -> &LazyLock<CommandQueue> =
+        LazyLock::new(|| CommandQueue::new(RenderCommandQueue::CAPACITY));
+}
+
+plugin_static! {
+    fn drain_event() -> &RenderEventRange = RenderEventRange::new();
+}
+
+/// A lock-free queue of commands that the main thread sends to the render thread.
+///
+/// The commands run on the render thread when C# issues the render event with ID
+/// [RenderCommandQueue::event_id] once per frame, using the function returned by the
+/// `GetRenderEventFunc` export of `#[unity_plugin]`. The ID is also exported to C#
+/// as `GetRenderCommandEventID`.
+///
+/// Pending commands are dropped without running when the graphics device
+/// is shut down or reset, because the resources they refer to are gone.
+pub struct RenderCommandQueue;
+
+impl RenderCommandQueue {
+    /// The maximum number of pending commands
+    pub const CAPACITY: usize = 1024;
+
+    /// Queues a command for the render thread. Fails when the queue is full,
+    /// which means the render thread is not keeping up, or nobody issues the drain event.
+    pub fn push<F: FnOnce(&RenderThread) + Send + 'static>(command: F) -> Result<(), QueueFull> {
+        queue().push(Box::new(command))
+    }
+
+    /// The number of pending commands
+    pub fn len() -> usize {
+        queue().commands.len()
+    }
+
+    /// Whether there are no pending commands
+    pub fn is_empty() -> bool {
+        queue().commands.is_empty()
+    }
+
+    /// The render event ID that drains the queue. It is reserved on first use,
+    /// so plugins that never use the queue do not take up an event ID.
+    pub fn event_id() -> Result<c_int, RenderEventErr> {
+        drain_event().get_or_reserve(1)
+    }
+}
+
+/// Drains the queue if the event is the drain event, returning whether it was
+pub(crate) fn drain_render_commands(render_thread: &RenderThread, event_id: c_int) -> bool {
+    if drain_event().get() != Some(event_id) {
+        return false;
+    }
+
+    queue().drain(render_thread);
+    true
+}
+
+pub(crate) fn flush_render_commands(event: GfxDeviceEvent) {
+    if matches!(
+        event,
+        GfxDeviceEvent::Shutdown | GfxDeviceEvent::BeforeReset
+    ) {
+        queue().clear();
+    }
+}
+
+pub(crate) fn clear_render_commands() {
+    queue().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn rejects_commands_when_full() {
+        let queue = CommandQueue::new(2);
+        let ran = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..2 {
+            let ran = ran.clone();
+            assert!(
+                queue
+                    .push(Box::new(move |_| {
+                        ran.fetch_add(1, Ordering::Relaxed);
+                    }))
+                    .is_ok()
+            );
+        }
+
+        let QueueFull(rejected) = queue.push(Box::new(|_| {})).unwrap_err();
+
+        queue.drain(&unsafe { RenderThread::new() });
+        assert_eq!(ran.load(Ordering::Relaxed), 2);
+        assert!(queue.push(rejected).is_ok());
+    }
+
+    #[test]
+    fn drops_commands_on_device_reset() {
+        let _mock = MockUnity::new();
+        RenderCommandQueue::push(|_| panic!("Must not run")).unwrap();
+        assert_eq!(RenderCommandQueue::len(), 1);
+
+        flush_render_commands(GfxDeviceEvent::AfterReset);
+        assert_eq!(RenderCommandQueue::len(), 1);
+
+        flush_render_commands(GfxDeviceEvent::BeforeReset);
+        assert!(RenderCommandQueue::is_empty());
+    }
+
+    #[test]
+    fn drains_on_render_thread() {
+        let mock = MockUnity::new();
+        crate::__private::plugin_loaded(&mock.interfaces());
+
+        let main_thread = std::thread::current().id();
+        let ran_on = Arc::new(std::sync::Mutex::new(None));
+
+        let ran = ran_on.clone();
+        RenderCommandQueue::push(move |_| {
+            *ran.lock().unwrap() = Some(std::thread::current().id());
+        })
+        .unwrap();
+
+        let event_id = RenderCommandQueue::event_id().unwrap();
+        std::thread::spawn(move || {
+            assert!(drain_render_commands(
+                &unsafe { RenderThread::new() },
+                event_id
+            ));
+        })
+        .join()
+        .unwrap();
+
+        let ran_on = ran_on.lock().unwrap().expect("Command did not run");
+        assert_ne!(ran_on, main_thread);
+        assert!(RenderCommandQueue::is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+use std::ffi::{CStr, c_char, c_void};
+use std::os::raw::{c_int, c_uint};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::{RenderThread, RenderingExtEvent};
+use crate::ffi;
+
+type BlitHandler = Arc<dyn Fn(&RenderThread, &CustomBlit) + Send + Sync>;
+
+/// The parameters of a custom blit issued from C# with
+/// `CommandBuffer.IssuePluginCustomBlit`, or of a grab into a render texture
+#[derive(Debug, Clone, Copy)]
+pub struct CustomBlit {
+    /// The native texture ID of the source texture
+    pub source: ffi::UnityTextureID,
+    /// The render buffer to blit into
+    pub destination: ffi::UnityRenderBuffer,
+    /// The `commandParam` the blit was issued with. Not set for grabs.
+    pub command_param: c_uint,
+    /// The `commandFlags` the blit was issued with. Not set for grabs.
+    pub command_flags: c_uint,
+}
+
+impl From<&ffi::UnityRenderingExtCustomBlitParams> for CustomBlit {
+    fn from(value: &ffi::UnityRenderingExtCustomBlitParams) -> Self {
+        Self {
+            source: value.source,
+            destination: value.destination,
+            command_param: value.commandParam,
+            command_flags: value.commandFlags,
+        }
+    }
+}
+
+struct Blits {
+    /// Named commands, with the command ID being the index plus `kUnityRenderingExtUserCustomBlitStart`
+    commands: Vec<(String, BlitHandler)>,
+    grab: Option<BlitHandler>,
+}
+
+plugin_static! {
+    fn blits() -> &Mutex<Blits> = Mutex::new(Blits {
+        commands: Vec::new(),
+        grab: None,
+    });
+}
+
+fn lock() -> MutexGuard<'static, Blits> {
+    blits().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+const USER_START: c_uint =
+    ffi::UnityRenderingExtCustomBlitCommands::kUnityRenderingExtUserCustomBlitStart.0;
+
+/// Named custom blit commands, run on the render thread for
+/// [RenderingExtEvent::CustomBlit].
+///
+/// Commands are numbered from `kUnityRenderingExtUserCustomBlitStart` in registration
+/// order. C# should not hardcode those numbers, but look them up by name through the
+/// `GetCustomBlitCommandID` export of `#[unity_plugin]`, and issue the blits with
+/// `CommandBuffer.IssuePluginCustomBlit` using the callback from `GetCustomBlitFunc`.
+/// Handled blits and grabs are not passed on to
+/// [UnityPlugin::on_rendering_ext_event](crate::UnityPlugin::on_rendering_ext_event).
+pub struct CustomBlits;
+
+impl CustomBlits {
+    /// Registers the handler for the command `name`, returning its command ID.
+    /// Registering a name again replaces its handler, but keeps the ID.
+    pub fn register<F>(name: &str, handler: F) -> c_uint
+    where
+        F: Fn(&RenderThread, &CustomBlit) + Send + Sync + 'static,
+    {
+        let mut blits = lock();
+        let handler: BlitHandler = Arc::new(handler);
+
+        let index = match blits.commands.iter().position(|(known, _)| known == name) {
+            Some(index) => {
+                blits.commands[index].1 = handler;
+                index
+            }
+            None => {
+                blits.commands.push((name.to_owned(), handler));
+                blits.commands.len() - 1
+            }
+        };
+
+        USER_START + index as c_uint
+    }
+
+    /// The command ID of a registered command
+    pub fn command_id(name: &str) -> Option<c_uint> {
+        lock()
+            .commands
+            .iter()
+            .position(|(known, _)| known == name)
+            .map(|index| USER_START + index as c_uint)
+    }
+
+    /// Registers the handler for [RenderingExtEvent::CustomGrab], which Unity sends
+    /// instead of copying into a render texture itself. Replaces any previous handler.
+    pub fn on_grab<F>(handler: F)
+    where
+        F: Fn(&RenderThread, &CustomBlit) + Send + Sync + 'static,
+    {
+        lock().grab = Some(Arc::new(handler));
+    }
+}
+
+/// Runs the handler of a registered blit command or grab, returning
+/// whether the event was handled
+pub(crate) fn handle_custom_blit(render_thread: &RenderThread, event: &RenderingExtEvent) -> bool {
+    // Not locked while running, so that handlers can register others
+    let (params, handler) = {
+        let blits = lock();
+
+        match event {
+            RenderingExtEvent::CustomBlit(params) => (
+                params,
+                params
+                    .command
+                    .checked_sub(USER_START)
+                    .and_then(|index| blits.commands.get(index as usize))
+                    .map(|(_, handler)| handler.clone()),
+            ),
+            RenderingExtEvent::CustomGrab(params) => (params, blits.grab.clone()),
+            _ => return false,
+        }
+    };
+
+    let Some(handler) = handler else {
+        return false;
+    };
+
+    handler(render_thread, &CustomBlit::from(*params));
+    true
+}
+
+/// Drops all commands at unload
+pub(crate) fn clear_custom_blits() {
+    let mut blits = lock();
+
+    blits.commands.clear();
+    blits.grab = None;
+}
+
+pub(crate) fn custom_blit_func() -> ffi::UnityRenderingEventAndData {
+    Some(custom_blit_trampoline)
+}
+
+/// Looks up the ID of a command for C#, or -1 if it is not registered
+///
+/// # Safety
+/// `name` must be NULL or a NUL terminated string
+pub(crate) unsafe fn custom_blit_command_id(name: *const c_char) -> c_int {
+    if name.is_null() {
+        return -1;
+    }
+
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+
+    CustomBlits::command_id(&name).map_or(-1, |id| id as c_int)
+}
+
+unity_api_fn! {
+    /// `IssuePluginCustomBlit` calls back with the rendering extension event and its parameters
+    unsafe fn custom_blit_trampoline(event_id: c_int, data: *mut c_void) {
+        crate::plugin::rendering_ext_event(
+            ffi::UnityRenderingExtEventType(event_id as c_uint),
+            data,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::mock::MockUnity;
+
+    static LAST_PARAM: AtomicU32 = AtomicU32::new(0);
+
+    #[test]
+    fn dispatches_named_commands() {
+        let _mock = MockUnity::new();
+        let render_thread = unsafe { RenderThread::new() };
+
+        let downscale = CustomBlits::register("downscale", |_, _| {});
+        let upscale = CustomBlits::register("upscale", |_, _| {});
+
+        // Replaced, keeping the ID
+        let replaced = CustomBlits::register("downscale", |_, blit| {
+            LAST_PARAM.store(blit.command_param, Ordering::Relaxed);
+        });
+
+        assert_eq!(downscale, USER_START);
+        assert_eq!(upscale, USER_START + 1);
+        assert_eq!(replaced, downscale);
+        assert_eq!(CustomBlits::command_id("upscale"), Some(upscale));
+        assert_eq!(
+            unsafe { custom_blit_command_id(c"downscale".as_ptr()) },
+            downscale as c_int
+        );
+        assert_eq!(unsafe { custom_blit_command_id(c"missing".as_ptr()) }, -1);
+
+        let params = ffi::UnityRenderingExtCustomBlitParams {
+            command: downscale,
+            commandParam: 42,
+            ..Default::default()
+        };
+        assert!(handle_custom_blit(
+            &render_thread,
+            &RenderingExtEvent::CustomBlit(&params)
+        ));
+        assert_eq!(LAST_PARAM.load(Ordering::Relaxed), 42);
+
+        let vr_flush = ffi::UnityRenderingExtCustomBlitParams::default();
+        assert!(!handle_custom_blit(
+            &render_thread,
+            &RenderingExtEvent::CustomBlit(&vr_flush)
+        ));
+        assert!(!handle_custom_blit(
+            &render_thread,
+            &RenderingExtEvent::CustomGrab(&vr_flush)
+        ));
+    }
+}
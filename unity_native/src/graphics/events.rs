@@ -0,0 +1,208 @@
+use std::sync::{Arc, RwLock};
+
+use thiserror::Error;
+
+use super::{GfxDeviceEvent, UnityGraphics};
+use crate::{deregister, ffi, lease};
+
+type DeviceEventClosure = Arc<dyn Fn(GfxDeviceEvent) + Send + Sync>;
+
+/// `IUnityGraphicsDeviceEventCallback` carries no user data, so a single trampoline
+/// is registered with Unity, which dispatches to all registered closures
+struct Dispatcher {
+    registered: bool,
+    next_id: u64,
+    closures: Vec<(u64, DeviceEventClosure)>,
+}
+
+plugin_static! {
+    fn dispatcher_state() -> &RwLock<Dispatcher> = RwLock::new(Dispatcher {
+        registered: false,
+        next_id: 0,
+        closures: Vec::new(),
+    });
+}
+
+/// An error while registering a device event closure
+#[derive(Debug, Error)]
+pub enum DeviceEventErr {
+    #[error("The plugin was unloaded")]
+    Unloaded,
+}
+
+/// A registered device event closure. The closure is removed when this is dropped,
+/// or when the plugin is unloaded, whichever comes first.
+#[must_use = "The closure is removed when the handle is dropped"]
+#[derive(Debug)]
+pub struct DeviceEventCallback {
+    id: Option<u64>,
+}
+
+impl DeviceEventCallback {
+    /// Keeps the closure registered for the rest of the plugin lifetime
+    pub fn forget(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for DeviceEventCallback {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            let mut dispatcher = dispatcher_state()
+                .write()
+                .unwrap_or_else(|e| e.into_inner());
+            dispatcher.closures.retain(|(other, _)| *other != id);
+        }
+    }
+}
+
+impl UnityGraphics {
+    /// Registers a closure that is called for every graphics device event.
+    ///
+    /// Unity does not repeat [GfxDeviceEvent::Initialize] for closures registered
+    /// after the graphics device was created, which includes closures registered
+    /// during plugin load, so check [UnityGraphics::renderer] for the current state.
+    pub fn on_device_event<F: Fn(GfxDeviceEvent) + Send + Sync + 'static>(
+        &self,
+        callback: F,
+    ) -> Result<DeviceEventCallback, DeviceEventErr> {
+        // The trampoline is claimed under the lock, but registered with Unity after
+        // releasing it, because Unity may call the trampoline during registration
+        let (id, claimed) = {
+            let mut dispatcher = dispatcher_state()
+                .write()
+                .unwrap_or_else(|e| e.into_inner());
+
+            let id = dispatcher.next_id;
+            dispatcher.next_id += 1;
+            dispatcher.closures.push((id, Arc::new(callback)));
+
+            (id, !std::mem::replace(&mut dispatcher.registered, true))
+        };
+
+        if claimed {
+            let registered = lease::with(self.generation, || unsafe {
+                (self.register_device_event_callback)(Some(device_event_trampoline))
+            });
+
+            if registered.is_none() {
+                let mut dispatcher = dispatcher_state()
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner());
+                dispatcher.registered = false;
+                dispatcher.closures.retain(|(other, _)| *other != id);
+
+                return Err(DeviceEventErr::Unloaded);
+            }
+
+            let graphics = self.clone();
+            deregister::track("graphics device event callback", move || {
+                lease::with(graphics.generation, || unsafe {
+                    (graphics.unregister_device_event_callback)(Some(device_event_trampoline))
+                });
+
+                let mut dispatcher = dispatcher_state()
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner());
+                dispatcher.registered = false;
+                dispatcher.closures.clear();
+
+                Ok(())
+            });
+        }
+
+        Ok(DeviceEventCallback { id: Some(id) })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn device_event_trampoline(event: ffi::UnityGfxDeviceEventType) {
+        // Cloned, so that closures can register or remove closures themselves
+        let closures: Vec<DeviceEventClosure> = dispatcher_state()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .closures
+            .iter()
+            .map(|(_, closure)| closure.clone())
+            .collect();
+
+        let event = GfxDeviceEvent::from(event);
+
+        for closure in closures {
+            crate::panic::guard("graphics device event", (), || closure(event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr::NonNull;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn dispatches_to_all_closures() {
+        let mock = MockUnity::new();
+        let graphics = mock.interfaces().get::<UnityGraphics>().unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let first_events = events.clone();
+        let first = graphics
+            .on_device_event(move |event| first_events.lock().unwrap().push((1, event)))
+            .unwrap();
+
+        let second_events = events.clone();
+        graphics
+            .on_device_event(move |event| second_events.lock().unwrap().push((2, event)))
+            .unwrap()
+            .forget();
+
+        assert_eq!(mock.device_event_callback_count(), 1);
+
+        mock.fire_device_event(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventBeforeReset);
+        drop(first);
+        mock.fire_device_event(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventAfterReset);
+
+        crate::__private::plugin_unloading();
+        assert_eq!(mock.device_event_callback_count(), 0);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                (1, GfxDeviceEvent::BeforeReset),
+                (2, GfxDeviceEvent::BeforeReset),
+                (2, GfxDeviceEvent::AfterReset),
+            ]
+        );
+    }
+
+    unity_api_fn! {
+        unsafe fn register_and_initialize(callback: ffi::IUnityGraphicsDeviceEventCallback) {
+            unsafe { callback.unwrap()(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventInitialize) };
+        }
+    }
+
+    #[test]
+    fn unity_may_call_back_during_registration() {
+        let mock = MockUnity::new();
+        let raw = mock
+            .interfaces()
+            .get_raw(&<UnityGraphics as crate::UnityInterface>::GUID)
+            .unwrap();
+
+        let mut table = unsafe { *raw.cast::<ffi::IUnityGraphics>().as_ref() };
+        table.RegisterDeviceEventCallback = Some(register_and_initialize);
+        let graphics = UnityGraphics::try_from(NonNull::from(&mut table)).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        graphics
+            .on_device_event(move |event| recorded.lock().unwrap().push(event))
+            .unwrap()
+            .forget();
+
+        assert_eq!(*events.lock().unwrap(), [GfxDeviceEvent::Initialize]);
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt::Display;
+use std::os::raw::{c_int, c_uint};
+use std::ptr::NonNull;
+
+use crate::{MissingFunctionsError, UnityInterface, ffi, lease};
+
+mod command_queue;
+mod custom_blit;
+mod events;
+mod pixel_convert;
+mod render_data;
+mod render_events;
+mod render_thread;
+mod rendering_ext;
+mod resources;
+mod texture_format;
+mod texture_update;
+
+pub use command_queue::*;
+pub use custom_blit::*;
+pub use events::*;
+pub use pixel_convert::*;
+pub use render_data::*;
+pub use render_events::*;
+pub use render_thread::*;
+pub use rendering_ext::*;
+pub use resources::*;
+pub use texture_format::*;
+pub use texture_update::*;
+
+type GetRendererFn = unity_api_fn_type!(fn() -> ffi::UnityGfxRenderer);
+type DeviceEventCallbackFn = unity_api_fn_type!(fn(ffi::IUnityGraphicsDeviceEventCallback));
+type ReserveEventIdRangeFn = unity_api_fn_type!(fn(c_int) -> c_int);
+
+/// A wrapper for the Unity Graphics API
+#[derive(Debug, Clone)]
+pub struct UnityGraphics {
+    get_renderer: GetRendererFn,
+    register_device_event_callback: DeviceEventCallbackFn,
+    unregister_device_event_callback: DeviceEventCallbackFn,
+    reserve_event_id_range: Option<ReserveEventIdRangeFn>,
+    generation: u64,
+}
+
+unsafe impl Send for UnityGraphics {}
+unsafe impl Sync for UnityGraphics {}
+
+unsafe impl UnityInterface for UnityGraphics {
+    type FFIType = ffi::IUnityGraphics;
+    type FFIConversionError = MissingFunctionsError;
+    const GUID: ffi::UnityInterfaceGUID = unity_api_guid!(0x7CBA0A9CA4DDB544 0x8C5AD4926EB17B11);
+}
+
+impl TryFrom<NonNull<ffi::IUnityGraphics>> for UnityGraphics {
+    type Error = MissingFunctionsError;
+
+    fn try_from(value: NonNull<ffi::IUnityGraphics>) -> Result<Self, Self::Error> {
+        let (get_renderer, register_device_event_callback, unregister_device_event_callback) = require_fns!(
+            "UnityGraphics",
+            unsafe { value.as_ref() },
+            GetRenderer,
+            RegisterDeviceEventCallback,
+            UnregisterDeviceEventCallback,
+        )?;
+
+        Ok(Self {
+            get_renderer,
+            register_device_event_callback,
+            unregister_device_event_callback,
+            reserve_event_id_range: unsafe { value.as_ref() }.ReserveEventIDRange,
+            generation: lease::current(),
+        })
+    }
+}
+
+impl UnityGraphics {
+    /// The graphics API Unity is currently rendering with. Thread safe.
+    /// Reports [GfxRenderer::Null] after the plugin was unloaded.
+    pub fn renderer(&self) -> GfxRenderer {
+        lease::with(self.generation, || unsafe { (self.get_renderer)() })
+            .map_or(GfxRenderer::Null, GfxRenderer::from)
+    }
+
+    /// Reserves a contiguous range of `count` render event IDs that no other plugin
+    /// will use, returning the first ID. See [RenderEvents] for a typed alternative.
+    pub fn reserve_event_ids(&self, count: c_int) -> Result<c_int, RenderEventErr> {
+        let reserve = self
+            .reserve_event_id_range
+            .ok_or(RenderEventErr::Unsupported)?;
+
+        lease::with(self.generation, || unsafe { reserve(count) }).ok_or(RenderEventErr::Unloaded)
+    }
+}
+
+/// The graphics device events Unity reports to plugins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GfxDeviceEvent {
+    /// The graphics device was created
+    Initialize,
+    /// The graphics device is about to be destroyed
+    Shutdown,
+    /// The graphics device is about to be reset, for example after the window was resized
+    BeforeReset,
+    /// The graphics device was reset
+    AfterReset,
+    /// An event not known to this version of the crate
+    Unknown(c_uint),
+}
+
+impl From<ffi::UnityGfxDeviceEventType> for GfxDeviceEvent {
+    fn from(value: ffi::UnityGfxDeviceEventType) -> Self {
+        match value {
+            ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventInitialize => {
+                GfxDeviceEvent::Initialize
+            }
+            ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventShutdown => GfxDeviceEvent::Shutdown,
+            ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventBeforeReset => {
+                GfxDeviceEvent::BeforeReset
+            }
+            ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventAfterReset => {
+                GfxDeviceEvent::AfterReset
+            }
+            other => GfxDeviceEvent::Unknown(other.0),
+        }
+    }
+}
+
+/// The graphics APIs Unity can render with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GfxRenderer {
+    D3D11,
+    /// The "null" device, used in batch mode
+    Null,
+    OpenGLES20,
+    OpenGLES30,
+    PS4,
+    XboxOne,
+    Metal,
+    OpenGLCore,
+    D3D12,
+    Vulkan,
+    /// The Nintendo Switch NVN API
+    Nvn,
+    XboxOneD3D12,
+    GameCoreXboxOne,
+    GameCoreXboxSeries,
+    PS5,
+    PS5NGGC,
+    /// A renderer not known to this version of the crate
+    Unknown(c_uint),
+}
+
+impl From<ffi::UnityGfxRenderer> for GfxRenderer {
+    fn from(value: ffi::UnityGfxRenderer) -> Self {
+        match value {
+            ffi::UnityGfxRenderer::kUnityGfxRendererD3D11 => GfxRenderer::D3D11,
+            ffi::UnityGfxRenderer::kUnityGfxRendererNull => GfxRenderer::Null,
+            ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLES20 => GfxRenderer::OpenGLES20,
+            ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLES30 => GfxRenderer::OpenGLES30,
+            ffi::UnityGfxRenderer::kUnityGfxRendererPS4 => GfxRenderer::PS4,
+            ffi::UnityGfxRenderer::kUnityGfxRendererXboxOne => GfxRenderer::XboxOne,
+            ffi::UnityGfxRenderer::kUnityGfxRendererMetal => GfxRenderer::Metal,
+            ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLCore => GfxRenderer::OpenGLCore,
+            ffi::UnityGfxRenderer::kUnityGfxRendererD3D12 => GfxRenderer::D3D12,
+            ffi::UnityGfxRenderer::kUnityGfxRendererVulkan => GfxRenderer::Vulkan,
+            ffi::UnityGfxRenderer::kUnityGfxRendererNvn => GfxRenderer::Nvn,
+            ffi::UnityGfxRenderer::kUnityGfxRendererXboxOneD3D12 => GfxRenderer::XboxOneD3D12,
+            ffi::UnityGfxRenderer::kUnityGfxRendererGameCoreXboxOne => GfxRenderer::GameCoreXboxOne,
+            ffi::UnityGfxRenderer::kUnityGfxRendererGameCoreXboxSeries => {
+                GfxRenderer::GameCoreXboxSeries
+            }
+            ffi::UnityGfxRenderer::kUnityGfxRendererPS5 => GfxRenderer::PS5,
+            ffi::UnityGfxRenderer::kUnityGfxRendererPS5NGGC => GfxRenderer::PS5NGGC,
+            other => GfxRenderer::Unknown(other.0),
+        }
+    }
+}
+
+impl From<GfxRenderer> for ffi::UnityGfxRenderer {
+    fn from(value: GfxRenderer) -> Self {
+        match value {
+            GfxRenderer::D3D11 => ffi::UnityGfxRenderer::kUnityGfxRendererD3D11,
+            GfxRenderer::Null => ffi::UnityGfxRenderer::kUnityGfxRendererNull,
+            GfxRenderer::OpenGLES20 => ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLES20,
+            GfxRenderer::OpenGLES30 => ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLES30,
+            GfxRenderer::PS4 => ffi::UnityGfxRenderer::kUnityGfxRendererPS4,
+            GfxRenderer::XboxOne => ffi::UnityGfxRenderer::kUnityGfxRendererXboxOne,
+            GfxRenderer::Metal => ffi::UnityGfxRenderer::kUnityGfxRendererMetal,
+            GfxRenderer::OpenGLCore => ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLCore,
+            GfxRenderer::D3D12 => ffi::UnityGfxRenderer::kUnityGfxRendererD3D12,
+            GfxRenderer::Vulkan => ffi::UnityGfxRenderer::kUnityGfxRendererVulkan,
+            GfxRenderer::Nvn => ffi::UnityGfxRenderer::kUnityGfxRendererNvn,
+            GfxRenderer::XboxOneD3D12 => ffi::UnityGfxRenderer::kUnityGfxRendererXboxOneD3D12,
+            GfxRenderer::GameCoreXboxOne => ffi::UnityGfxRenderer::kUnityGfxRendererGameCoreXboxOne,
+            GfxRenderer::GameCoreXboxSeries => {
+                ffi::UnityGfxRenderer::kUnityGfxRendererGameCoreXboxSeries
+            }
+            GfxRenderer::PS5 => ffi::UnityGfxRenderer::kUnityGfxRendererPS5,
+            GfxRenderer::PS5NGGC => ffi::UnityGfxRenderer::kUnityGfxRendererPS5NGGC,
+            GfxRenderer::Unknown(raw) => ffi::UnityGfxRenderer(raw),
+        }
+    }
+}
+
+impl Display for GfxRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GfxRenderer::D3D11 => f.write_str("Direct3D 11"),
+            GfxRenderer::Null => f.write_str("Null"),
+            GfxRenderer::OpenGLES20 => f.write_str("OpenGL ES 2.0"),
+            GfxRenderer::OpenGLES30 => f.write_str("OpenGL ES 3.0"),
+            GfxRenderer::PS4 => f.write_str("PlayStation 4"),
+            GfxRenderer::XboxOne => f.write_str("Xbox One"),
+            GfxRenderer::Metal => f.write_str("Metal"),
+            GfxRenderer::OpenGLCore => f.write_str("OpenGL Core"),
+            GfxRenderer::D3D12 => f.write_str("Direct3D 12"),
+            GfxRenderer::Vulkan => f.write_str("Vulkan"),
+            GfxRenderer::Nvn => f.write_str("NVN"),
+            GfxRenderer::XboxOneD3D12 => f.write_str("Xbox One Direct3D 12"),
+            GfxRenderer::GameCoreXboxOne => f.write_str("GameCore Xbox One"),
+            GfxRenderer::GameCoreXboxSeries => f.write_str("GameCore Xbox Series"),
+            GfxRenderer::PS5 => f.write_str("PlayStation 5"),
+            GfxRenderer::PS5NGGC => f.write_str("PlayStation 5 NGGC"),
+            GfxRenderer::Unknown(raw) => write!(f, "Unknown ({raw})"),
+        }
+    }
+}
@@ -0,0 +1,749 @@
+use half::f16;
+use thiserror::Error;
+
+use super::TextureFormat;
+
+/// The number of pixels converted at once, going through linear floating point RGBA
+const BATCH: usize = 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConvertErr {
+    #[error("Converting pixels from or to {0:?} is not supported")]
+    Unsupported(TextureFormat),
+
+    #[error("Expected {expected} bytes, but the buffer holds {actual}")]
+    Length { expected: usize, actual: usize },
+}
+
+/// Converts tightly packed pixels from `src_format` to `dst_format`, for example
+/// a row of a texture before handing it to a [TextureUpdater](super::TextureUpdater).
+///
+/// The number of pixels is taken from the length of `src`, which must be a multiple of
+/// the pixel size. Colors go through linear floating point RGBA, so sRGB formats are
+/// encoded and decoded, and missing alpha channels read as fully opaque.
+/// Conversions that only reorder the bytes of a pixel are done directly.
+///
+/// Supports the uncompressed UNorm, sRGB and float color formats, including the 16 bit
+/// and 32 bit packed ones. Integer, SNorm, depth, stencil and compressed formats are
+/// reported as [ConvertErr::Unsupported].
+pub fn convert_pixels(
+    src_format: TextureFormat,
+    src: &[u8],
+    dst_format: TextureFormat,
+    dst: &mut [u8],
+) -> Result<(), ConvertErr> {
+    let src_codec = codec(src_format).ok_or(ConvertErr::Unsupported(src_format))?;
+    let dst_codec = codec(dst_format).ok_or(ConvertErr::Unsupported(dst_format))?;
+
+    let pixels = src.len() / src_codec.bytes;
+    check_length(src, pixels * src_codec.bytes)?;
+    check_length(dst, pixels * dst_codec.bytes)?;
+
+    if src_format == dst_format {
+        dst.copy_from_slice(src);
+        return Ok(());
+    }
+
+    if let Some(order) = byte_swizzle(src_format, dst_format) {
+        swizzle_bytes(src, dst, order);
+        return Ok(());
+    }
+
+    let mut linear = [[0.0; 4]; BATCH];
+
+    for (src, dst) in src
+        .chunks(BATCH * src_codec.bytes)
+        .zip(dst.chunks_mut(BATCH * dst_codec.bytes))
+    {
+        let linear = &mut linear[..src.len() / src_codec.bytes];
+
+        (src_codec.decode_row)(src, linear);
+        (dst_codec.encode_row)(linear, dst);
+    }
+
+    Ok(())
+}
+
+fn check_length(buffer: &[u8], expected: usize) -> Result<(), ConvertErr> {
+    if buffer.len() == expected {
+        Ok(())
+    } else {
+        Err(ConvertErr::Length {
+            expected,
+            actual: buffer.len(),
+        })
+    }
+}
+
+/// Decodes a single sRGB encoded channel value to linear
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a single linear channel value as sRGB
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// For every destination byte, the source byte it is copied from
+fn byte_swizzle(src: TextureFormat, dst: TextureFormat) -> Option<[usize; 4]> {
+    use TextureFormat as F;
+
+    let order = |format| match format {
+        F::R8G8B8A8_UNorm | F::R8G8B8A8_SRGB => Some([0, 1, 2, 3]),
+        F::B8G8R8A8_UNorm | F::B8G8R8A8_SRGB => Some([2, 1, 0, 3]),
+        F::A8R8G8B8_UNorm | F::A8R8G8B8_SRGB => Some([3, 0, 1, 2]),
+        _ => None,
+    };
+
+    if src.is_srgb() != dst.is_srgb() {
+        return None;
+    }
+
+    // Both map byte index to RGBA channel, so compose src with the inverse of dst
+    let (src_order, dst_order) = (order(src)?, order(dst)?);
+    let mut swizzle = [0; 4];
+
+    for (dst_byte, channel) in dst_order.iter().enumerate() {
+        swizzle[dst_byte] = src_order.iter().position(|c| c == channel)?;
+    }
+
+    Some(swizzle)
+}
+
+fn swizzle_bytes(src: &[u8], dst: &mut [u8], order: [usize; 4]) {
+    for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        dst.copy_from_slice(&[src[order[0]], src[order[1]], src[order[2]], src[order[3]]]);
+    }
+}
+
+type DecodeRow = fn(&[u8], &mut [[f32; 4]]);
+type EncodeRow = fn(&[[f32; 4]], &mut [u8]);
+
+struct Codec {
+    bytes: usize,
+    decode_row: DecodeRow,
+    encode_row: EncodeRow,
+}
+
+/// A pixel layout, converted from and to linear RGBA
+trait Pixel {
+    const BYTES: usize;
+
+    fn decode(bytes: &[u8]) -> [f32; 4];
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]);
+}
+
+/// Monomorphised per layout, so that the loops can be vectorised
+fn decode_row<P: Pixel>(src: &[u8], dst: &mut [[f32; 4]]) {
+    for (bytes, rgba) in src.chunks_exact(P::BYTES).zip(dst) {
+        *rgba = P::decode(bytes);
+    }
+}
+
+fn encode_row<P: Pixel>(src: &[[f32; 4]], dst: &mut [u8]) {
+    for (rgba, bytes) in src.iter().zip(dst.chunks_exact_mut(P::BYTES)) {
+        P::encode(*rgba, bytes);
+    }
+}
+
+fn codec_of<P: Pixel>() -> Codec {
+    Codec {
+        bytes: P::BYTES,
+        decode_row: decode_row::<P>,
+        encode_row: encode_row::<P>,
+    }
+}
+
+/// Marks a channel that is not stored, for the channel indices of the generic layouts
+const NONE: usize = usize::MAX;
+
+fn codec(format: TextureFormat) -> Option<Codec> {
+    use TextureFormat as F;
+
+    Some(match format {
+        F::R8_UNorm => codec_of::<Unorm8<1, 0, NONE, NONE, NONE, false>>(),
+        F::R8G8_UNorm => codec_of::<Unorm8<2, 0, 1, NONE, NONE, false>>(),
+        F::R8G8B8_UNorm => codec_of::<Unorm8<3, 0, 1, 2, NONE, false>>(),
+        F::R8G8B8A8_UNorm => codec_of::<Unorm8<4, 0, 1, 2, 3, false>>(),
+        F::R8_SRGB => codec_of::<Unorm8<1, 0, NONE, NONE, NONE, true>>(),
+        F::R8G8_SRGB => codec_of::<Unorm8<2, 0, 1, NONE, NONE, true>>(),
+        F::R8G8B8_SRGB => codec_of::<Unorm8<3, 0, 1, 2, NONE, true>>(),
+        F::R8G8B8A8_SRGB => codec_of::<Unorm8<4, 0, 1, 2, 3, true>>(),
+        F::B8G8R8_UNorm => codec_of::<Unorm8<3, 2, 1, 0, NONE, false>>(),
+        F::B8G8R8A8_UNorm => codec_of::<Unorm8<4, 2, 1, 0, 3, false>>(),
+        F::B8G8R8_SRGB => codec_of::<Unorm8<3, 2, 1, 0, NONE, true>>(),
+        F::B8G8R8A8_SRGB => codec_of::<Unorm8<4, 2, 1, 0, 3, true>>(),
+        F::A8R8G8B8_UNorm => codec_of::<Unorm8<4, 1, 2, 3, 0, false>>(),
+        F::A8R8G8B8_SRGB => codec_of::<Unorm8<4, 1, 2, 3, 0, true>>(),
+        F::A8_UNorm => codec_of::<Unorm8<1, NONE, NONE, NONE, 0, false>>(),
+        F::L8_UNorm => codec_of::<Luminance8>(),
+
+        F::R16_UNorm => codec_of::<Unorm16<1, 0, NONE, NONE, NONE>>(),
+        F::R16G16_UNorm => codec_of::<Unorm16<2, 0, 1, NONE, NONE>>(),
+        F::R16G16B16_UNorm => codec_of::<Unorm16<3, 0, 1, 2, NONE>>(),
+        F::R16G16B16A16_UNorm => codec_of::<Unorm16<4, 0, 1, 2, 3>>(),
+        F::A16_UNorm => codec_of::<Unorm16<1, NONE, NONE, NONE, 0>>(),
+
+        F::R16_SFloat => codec_of::<Float16<1>>(),
+        F::R16G16_SFloat => codec_of::<Float16<2>>(),
+        F::R16G16B16_SFloat => codec_of::<Float16<3>>(),
+        F::R16G16B16A16_SFloat => codec_of::<Float16<4>>(),
+
+        F::R32_SFloat => codec_of::<Float32<1, 0, NONE, NONE, NONE>>(),
+        F::R32G32_SFloat => codec_of::<Float32<2, 0, 1, NONE, NONE>>(),
+        F::R32G32B32_SFloat => codec_of::<Float32<3, 0, 1, 2, NONE>>(),
+        F::R32G32B32A32_SFloat => codec_of::<Float32<4, 0, 1, 2, 3>>(),
+        F::A32R32G32B32_SFloat => codec_of::<Float32<4, 1, 2, 3, 0>>(),
+
+        F::R5G6B5_UNormPack16 => codec_of::<R5G6B5>(),
+        F::B5G6R5_UNormPack16 => codec_of::<B5G6R5>(),
+        F::R4G4B4A4_UNormPack16 => codec_of::<R4G4B4A4>(),
+        F::B4G4R4A4_UNormPack16 => codec_of::<B4G4R4A4>(),
+        F::R5G5B5A1_UNormPack16 => codec_of::<R5G5B5A1>(),
+        F::B5G5R5A1_UNormPack16 => codec_of::<B5G5R5A1>(),
+        F::A1R5G5B5_UNormPack16 => codec_of::<A1R5G5B5>(),
+        F::A2B10G10R10_UNormPack32 => codec_of::<A2B10G10R10>(),
+        F::A2R10G10B10_UNormPack32 => codec_of::<A2R10G10B10>(),
+
+        F::B10G11R11_UFloatPack32 => codec_of::<B10G11R11>(),
+        F::E5B9G9R9_UFloatPack32 => codec_of::<E5B9G9R9>(),
+
+        _ => return None,
+    })
+}
+
+/// Reads the channel stored at `index`, or `default` when it is not stored
+fn channel<T: Copy>(values: &[T], index: usize, default: T) -> T {
+    values.get(index).copied().unwrap_or(default)
+}
+
+fn to_unorm(value: f32, max: u32) -> u32 {
+    (value.clamp(0.0, 1.0) * max as f32 + 0.5) as u32
+}
+
+/// 8 bit normalized channels. `R`, `G`, `B` and `A` are the byte index of each channel.
+struct Unorm8<
+    const N: usize,
+    const R: usize,
+    const G: usize,
+    const B: usize,
+    const A: usize,
+    const SRGB: bool,
+>;
+
+impl<
+    const N: usize,
+    const R: usize,
+    const G: usize,
+    const B: usize,
+    const A: usize,
+    const SRGB: bool,
+> Pixel for Unorm8<N, R, G, B, A, SRGB>
+{
+    const BYTES: usize = N;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let color = |index| {
+            let value = f32::from(channel(bytes, index, 0)) / 255.0;
+            if SRGB { srgb_to_linear(value) } else { value }
+        };
+
+        [
+            color(R),
+            color(G),
+            color(B),
+            f32::from(channel(bytes, A, 255)) / 255.0,
+        ]
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        for (index, value) in [(R, rgba[0]), (G, rgba[1]), (B, rgba[2])] {
+            if let Some(byte) = bytes.get_mut(index) {
+                let value = if SRGB { linear_to_srgb(value) } else { value };
+                *byte = to_unorm(value, 255) as u8;
+            }
+        }
+
+        if let Some(byte) = bytes.get_mut(A) {
+            *byte = to_unorm(rgba[3], 255) as u8;
+        }
+    }
+}
+
+/// A single 8 bit channel, read as gray and written as the luminance of the color
+struct Luminance8;
+
+impl Pixel for Luminance8 {
+    const BYTES: usize = 1;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let value = f32::from(bytes[0]) / 255.0;
+        [value, value, value, 1.0]
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        let luminance = 0.2126 * rgba[0] + 0.7152 * rgba[1] + 0.0722 * rgba[2];
+        bytes[0] = to_unorm(luminance, 255) as u8;
+    }
+}
+
+/// 16 bit normalized channels, with the channel index of `R`, `G`, `B` and `A`
+struct Unorm16<const N: usize, const R: usize, const G: usize, const B: usize, const A: usize>;
+
+impl<const N: usize, const R: usize, const G: usize, const B: usize, const A: usize> Pixel
+    for Unorm16<N, R, G, B, A>
+{
+    const BYTES: usize = N * 2;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let mut values = [0u16; 4];
+        for (value, bytes) in values.iter_mut().zip(bytes.chunks_exact(2)) {
+            *value = u16::from_ne_bytes([bytes[0], bytes[1]]);
+        }
+
+        let values = &values[..N];
+        let read = |index, default| f32::from(channel(values, index, default)) / 65535.0;
+
+        [read(R, 0), read(G, 0), read(B, 0), read(A, u16::MAX)]
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        for (index, value) in [(R, rgba[0]), (G, rgba[1]), (B, rgba[2]), (A, rgba[3])] {
+            if index < N {
+                let value = to_unorm(value, 65535) as u16;
+                bytes[index * 2..index * 2 + 2].copy_from_slice(&value.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// Half float channels in RGBA order
+struct Float16<const N: usize>;
+
+impl<const N: usize> Pixel for Float16<N> {
+    const BYTES: usize = N * 2;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let mut rgba = [0.0, 0.0, 0.0, 1.0];
+        for (value, bytes) in rgba.iter_mut().zip(bytes.chunks_exact(2)) {
+            *value = f16::from_ne_bytes([bytes[0], bytes[1]]).to_f32();
+        }
+
+        rgba
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        for (value, bytes) in rgba.iter().zip(bytes.chunks_exact_mut(2)) {
+            bytes.copy_from_slice(&f16::from_f32(*value).to_ne_bytes());
+        }
+    }
+}
+
+/// Single precision float channels, with the channel index of `R`, `G`, `B` and `A`
+struct Float32<const N: usize, const R: usize, const G: usize, const B: usize, const A: usize>;
+
+impl<const N: usize, const R: usize, const G: usize, const B: usize, const A: usize> Pixel
+    for Float32<N, R, G, B, A>
+{
+    const BYTES: usize = N * 4;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let mut values = [0.0; 4];
+        for (value, bytes) in values.iter_mut().zip(bytes.chunks_exact(4)) {
+            *value = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let values = &values[..N];
+        [
+            channel(values, R, 0.0),
+            channel(values, G, 0.0),
+            channel(values, B, 0.0),
+            channel(values, A, 1.0),
+        ]
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        for (index, value) in [(R, rgba[0]), (G, rgba[1]), (B, rgba[2]), (A, rgba[3])] {
+            if index < N {
+                bytes[index * 4..index * 4 + 4].copy_from_slice(&value.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// Declares a packed normalized format. Every channel is given as `(shift, bits)`,
+/// with 0 bits for a missing alpha channel. As in the format names, the first
+/// channel is stored in the most significant bits.
+macro_rules! packed_unorm {
+    ($($name:ident: $int:ty => [$(($shift:literal, $bits:literal)),*];)*) => {
+        $(
+            struct $name;
+
+            impl Pixel for $name {
+                const BYTES: usize = size_of::<$int>();
+
+                fn decode(bytes: &[u8]) -> [f32; 4] {
+                    let packed = u32::from(<$int>::from_ne_bytes(bytes.try_into().unwrap()));
+                    let [r, g, b, a] = [$(($shift, $bits)),*];
+
+                    [
+                        unpack(packed, r, 0.0),
+                        unpack(packed, g, 0.0),
+                        unpack(packed, b, 0.0),
+                        unpack(packed, a, 1.0),
+                    ]
+                }
+
+                fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+                    let packed = [$(($shift, $bits)),*]
+                        .into_iter()
+                        .zip(rgba)
+                        .fold(0, |packed, (field, value)| packed | pack(value, field));
+
+                    bytes.copy_from_slice(&(packed as $int).to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+fn unpack(packed: u32, (shift, bits): (u32, u32), default: f32) -> f32 {
+    if bits == 0 {
+        return default;
+    }
+
+    let max = (1 << bits) - 1;
+    ((packed >> shift) & max) as f32 / max as f32
+}
+
+fn pack(value: f32, (shift, bits): (u32, u32)) -> u32 {
+    if bits == 0 {
+        return 0;
+    }
+
+    to_unorm(value, (1 << bits) - 1) << shift
+}
+
+// Channels in RGBA order
+packed_unorm! {
+    R5G6B5: u16 => [(11, 5), (5, 6), (0, 5), (0, 0)];
+    B5G6R5: u16 => [(0, 5), (5, 6), (11, 5), (0, 0)];
+    R4G4B4A4: u16 => [(12, 4), (8, 4), (4, 4), (0, 4)];
+    B4G4R4A4: u16 => [(4, 4), (8, 4), (12, 4), (0, 4)];
+    R5G5B5A1: u16 => [(11, 5), (6, 5), (1, 5), (0, 1)];
+    B5G5R5A1: u16 => [(1, 5), (6, 5), (11, 5), (0, 1)];
+    A1R5G5B5: u16 => [(10, 5), (5, 5), (0, 5), (15, 1)];
+    A2B10G10R10: u32 => [(0, 10), (10, 10), (20, 10), (30, 2)];
+    A2R10G10B10: u32 => [(20, 10), (10, 10), (0, 10), (30, 2)];
+}
+
+/// Unsigned 11 and 10 bit floats with a 5 bit exponent, blue in the most significant bits
+struct B10G11R11;
+
+impl Pixel for B10G11R11 {
+    const BYTES: usize = 4;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let packed = u32::from_ne_bytes(bytes.try_into().unwrap());
+
+        [
+            decode_ufloat(packed & 0x7FF, 6),
+            decode_ufloat((packed >> 11) & 0x7FF, 6),
+            decode_ufloat(packed >> 22, 5),
+            1.0,
+        ]
+    }
+
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        let packed = encode_ufloat(rgba[0], 6)
+            | (encode_ufloat(rgba[1], 6) << 11)
+            | (encode_ufloat(rgba[2], 5) << 22);
+
+        bytes.copy_from_slice(&packed.to_ne_bytes());
+    }
+}
+
+/// Decodes an unsigned float with a 5 bit exponent and `mantissa_bits` of mantissa
+fn decode_ufloat(value: u32, mantissa_bits: u32) -> f32 {
+    let exponent = value >> mantissa_bits;
+    let mantissa = (value & ((1 << mantissa_bits) - 1)) as f32 / (1 << mantissa_bits) as f32;
+
+    match exponent {
+        0 => mantissa * 2f32.powi(-14),
+        31 if mantissa == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (1.0 + mantissa) * 2f32.powi(exponent as i32 - 15),
+    }
+}
+
+/// Encodes an unsigned float with a 5 bit exponent and `mantissa_bits` of mantissa,
+/// rounding to nearest and clamping negative values to 0 and large ones to the maximum
+fn encode_ufloat(value: f32, mantissa_bits: u32) -> u32 {
+    // The largest finite value, with exponent 30 and a full mantissa
+    let max = (30 << mantissa_bits) | ((1 << mantissa_bits) - 1);
+
+    if value.is_nan() || value <= 0.0 {
+        return 0;
+    }
+
+    let exponent = ((value.to_bits() >> 23) & 0xFF) as i32 - 127;
+
+    let encoded = if exponent < -14 {
+        // Denormal. Rounding up into the smallest normal value carries into the exponent.
+        (value * 2f32.powi(14 + mantissa_bits as i32)).round() as u32
+    } else {
+        let shift = 23 - mantissa_bits;
+        let mantissa = ((value.to_bits() & 0x7F_FFFF) + (1 << (shift - 1))) >> shift;
+
+        (((exponent + 15) as u32) << mantissa_bits) + mantissa
+    };
+
+    encoded.min(max)
+}
+
+/// Three 9 bit mantissas sharing a 5 bit exponent, blue in the most significant bits
+struct E5B9G9R9;
+
+impl E5B9G9R9 {
+    const MANTISSA_BITS: i32 = 9;
+    const BIAS: i32 = 15;
+    const MAX: f32 = 511.0 / 512.0 * 65536.0;
+}
+
+impl Pixel for E5B9G9R9 {
+    const BYTES: usize = 4;
+
+    fn decode(bytes: &[u8]) -> [f32; 4] {
+        let packed = u32::from_ne_bytes(bytes.try_into().unwrap());
+        let scale = 2f32.powi((packed >> 27) as i32 - Self::BIAS - Self::MANTISSA_BITS);
+
+        [
+            (packed & 0x1FF) as f32 * scale,
+            ((packed >> 9) & 0x1FF) as f32 * scale,
+            ((packed >> 18) & 0x1FF) as f32 * scale,
+            1.0,
+        ]
+    }
+
+    /// Follows `EXT_texture_shared_exponent`
+    fn encode(rgba: [f32; 4], bytes: &mut [u8]) {
+        let [r, g, b] = [rgba[0], rgba[1], rgba[2]].map(|value| {
+            if value.is_nan() {
+                0.0
+            } else {
+                value.clamp(0.0, Self::MAX)
+            }
+        });
+
+        let max = r.max(g).max(b);
+        let mut exponent = (max.log2().floor() as i32).max(-Self::BIAS - 1) + 1 + Self::BIAS;
+
+        let mut scale = 2f32.powi(exponent - Self::BIAS - Self::MANTISSA_BITS);
+        if (max / scale + 0.5).floor() as i32 == 1 << Self::MANTISSA_BITS {
+            exponent += 1;
+            scale *= 2.0;
+        }
+
+        let [r, g, b] = [r, g, b].map(|value| (value / scale + 0.5).floor() as u32);
+        let packed = ((exponent as u32) << 27) | (b << 18) | (g << 9) | r;
+
+        bytes.copy_from_slice(&packed.to_ne_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(src_format: TextureFormat, src: &[u8], dst_format: TextureFormat) -> Vec<u8> {
+        let bytes = src.len() / codec(src_format).unwrap().bytes * codec(dst_format).unwrap().bytes;
+        let mut dst = vec![0; bytes];
+
+        convert_pixels(src_format, src, dst_format, &mut dst).unwrap();
+        dst
+    }
+
+    fn from_f32(format: TextureFormat, rgba: [f32; 4]) -> Vec<u8> {
+        let src: Vec<u8> = rgba.iter().flat_map(|value| value.to_ne_bytes()).collect();
+        convert(TextureFormat::R32G32B32A32_SFloat, &src, format)
+    }
+
+    fn to_f32(format: TextureFormat, src: &[u8]) -> [f32; 4] {
+        let dst = convert(format, src, TextureFormat::R32G32B32A32_SFloat);
+        std::array::from_fn(|i| f32::from_ne_bytes(dst[i * 4..i * 4 + 4].try_into().unwrap()))
+    }
+
+    #[test]
+    fn swizzles_bytes() {
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        assert_eq!(
+            convert(
+                TextureFormat::R8G8B8A8_UNorm,
+                &rgba,
+                TextureFormat::B8G8R8A8_UNorm
+            ),
+            [3, 2, 1, 4, 7, 6, 5, 8]
+        );
+        assert_eq!(
+            convert(
+                TextureFormat::R8G8B8A8_SRGB,
+                &rgba,
+                TextureFormat::A8R8G8B8_SRGB
+            ),
+            [4, 1, 2, 3, 8, 5, 6, 7]
+        );
+        assert_eq!(
+            convert(
+                TextureFormat::R8G8B8A8_UNorm,
+                &rgba,
+                TextureFormat::R8G8B8_UNorm
+            ),
+            [1, 2, 3, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn converts_srgb() {
+        assert_eq!(
+            convert(
+                TextureFormat::R8G8B8A8_SRGB,
+                &[128, 0, 255, 128],
+                TextureFormat::R8G8B8A8_UNorm
+            ),
+            [55, 0, 255, 128]
+        );
+        assert_eq!(
+            from_f32(TextureFormat::R8_SRGB, [0.5, 0.0, 0.0, 1.0]),
+            [188]
+        );
+        assert!((srgb_to_linear(linear_to_srgb(0.25)) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn converts_half_floats() {
+        let half = from_f32(
+            TextureFormat::R16G16B16A16_SFloat,
+            [1.0, 0.5, -2.0, 65504.0],
+        );
+        let half: Vec<u16> = half
+            .chunks_exact(2)
+            .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+            .collect();
+
+        assert_eq!(half, [0x3C00, 0x3800, 0xC000, 0x7BFF]);
+    }
+
+    #[test]
+    fn converts_packed_16_bit() {
+        let packed = |format, rgba| u16::from_ne_bytes(from_f32(format, rgba).try_into().unwrap());
+
+        assert_eq!(
+            packed(TextureFormat::R5G6B5_UNormPack16, [1.0, 0.0, 0.0, 1.0]),
+            0xF800
+        );
+        assert_eq!(
+            packed(TextureFormat::R5G6B5_UNormPack16, [0.0, 1.0, 0.0, 1.0]),
+            0x07E0
+        );
+        assert_eq!(
+            packed(TextureFormat::B5G6R5_UNormPack16, [1.0, 0.0, 0.0, 1.0]),
+            0x001F
+        );
+        assert_eq!(
+            packed(TextureFormat::R4G4B4A4_UNormPack16, [1.0, 0.0, 0.0, 1.0]),
+            0xF00F
+        );
+        assert_eq!(
+            packed(TextureFormat::A1R5G5B5_UNormPack16, [0.0, 0.0, 1.0, 1.0]),
+            0x801F
+        );
+
+        assert_eq!(
+            to_f32(TextureFormat::R5G6B5_UNormPack16, &0x07E0u16.to_ne_bytes()),
+            [0.0, 1.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn converts_packed_floats() {
+        let packed = |format, rgba| u32::from_ne_bytes(from_f32(format, rgba).try_into().unwrap());
+
+        assert_eq!(
+            packed(TextureFormat::B10G11R11_UFloatPack32, [1.0, 1.0, 1.0, 1.0]),
+            0x781E03C0
+        );
+        assert_eq!(
+            packed(TextureFormat::E5B9G9R9_UFloatPack32, [1.0, 1.0, 1.0, 1.0]),
+            0x84020100
+        );
+        assert_eq!(
+            packed(TextureFormat::B10G11R11_UFloatPack32, [-1.0, 1e9, 0.0, 1.0]),
+            0x7BF << 11
+        );
+
+        let rgba = [0.25, 3.5, 100.0, 1.0];
+        assert_eq!(
+            to_f32(
+                TextureFormat::B10G11R11_UFloatPack32,
+                &packed(TextureFormat::B10G11R11_UFloatPack32, rgba).to_ne_bytes()
+            ),
+            rgba
+        );
+        assert_eq!(
+            to_f32(
+                TextureFormat::E5B9G9R9_UFloatPack32,
+                &packed(TextureFormat::E5B9G9R9_UFloatPack32, rgba).to_ne_bytes()
+            ),
+            rgba
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        let mut dst = [0; 16];
+
+        assert_eq!(
+            convert_pixels(
+                TextureFormat::RGBA_BC7_UNorm,
+                &[0; 16],
+                TextureFormat::R8G8B8A8_UNorm,
+                &mut dst
+            ),
+            Err(ConvertErr::Unsupported(TextureFormat::RGBA_BC7_UNorm))
+        );
+        assert_eq!(
+            convert_pixels(
+                TextureFormat::R8G8B8A8_UNorm,
+                &[0; 7],
+                TextureFormat::R8G8B8A8_UNorm,
+                &mut dst
+            ),
+            Err(ConvertErr::Length {
+                expected: 4,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            convert_pixels(
+                TextureFormat::R8G8B8A8_UNorm,
+                &[0; 8],
+                TextureFormat::R8G8B8A8_SRGB,
+                &mut dst
+            ),
+            Err(ConvertErr::Length {
+                expected: 8,
+                actual: 16
+            })
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+use super::RenderThread;
+use crate::ffi;
+use crate::panic::{guard, log_error};
+
+type DataHandler = Arc<dyn Fn(&RenderThread, &(dyn Any + Send)) + Send + Sync>;
+
+/// A value allocated with [FrameArena::alloc], waiting to be consumed by the render thread
+struct Allocation {
+    frame: u64,
+    value: Box<dyn Any + Send>,
+}
+
+struct Arena {
+    frame: u64,
+    /// The key of the next allocation. Only ever increases, so a stale pointer
+    /// from C# never refers to a newer allocation.
+    next_key: usize,
+    allocations: HashMap<usize, Allocation>,
+}
+
+plugin_static! {
+    fn handlers() -> &LazyLock<RwLock<HashMap<c_int, DataHandler>>> =
+        LazyLock::new(|| RwLock::new(HashMap::new()));
+}
+
+plugin_static! {
+    fn arena() -> &LazyLock<Mutex<Arena>> = LazyLock::new(|| {
+        Mutex::new(Arena {
+            frame: 0,
+            next_key: 1,
+            allocations: HashMap::new(),
+        })
+    });
+}
+
+/// Registers the handler for render events issued from C# with
+/// `CommandBuffer.IssuePluginEventAndData`, using the function returned by the
+/// `GetRenderEventAndDataFunc` export of `#[unity_plugin]`.
+///
+/// The data pointer must be allocated with [FrameArena::alloc]. It is checked to be
+/// a live allocation of type `T` before the handler is called on the render thread.
+/// Replaces any handler previously registered for the same event ID.
+pub fn on_render_event_data<T: Send + 'static>(event_id: c_int, handler: fn(&RenderThread, &T)) {
+    let handler: DataHandler =
+        Arc::new(move |render_thread, data| match data.downcast_ref::<T>() {
+            Some(data) => handler(render_thread, data),
+            None => log_error(&format!(
+                "Render event {} received data that is not a {}",
+                event_id,
+                std::any::type_name::<T>()
+            )),
+        });
+
+    handlers()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(event_id, handler);
+}
+
+/// Frame-scoped storage for the data passed along with render events.
+///
+/// The main thread allocates a value and hands the returned pointer to C#, which
+/// passes it to `IssuePluginEventAndData`. The value is freed right after the render
+/// thread has handled the event. Values that are never consumed, for example because
+/// the command buffer was not executed, are freed by [FrameArena::end_frame].
+pub struct FrameArena;
+
+impl FrameArena {
+    /// The number of frames an allocation may stay unconsumed, because the
+    /// render thread can run behind the main thread
+    pub const FRAMES_IN_FLIGHT: u64 = 2;
+
+    /// Moves the value into the arena, returning the pointer to pass to C#.
+    /// The pointer is an opaque key for the value, not its address.
+    pub fn alloc<T: Send + 'static>(value: T) -> *mut c_void {
+        let value: Box<dyn Any + Send> = Box::new(value);
+
+        let mut arena = arena().lock().unwrap_or_else(|e| e.into_inner());
+        let frame = arena.frame;
+        let key = arena.next_key;
+        arena.next_key += 1;
+        arena.allocations.insert(key, Allocation { frame, value });
+
+        std::ptr::without_provenance_mut(key)
+    }
+
+    /// Marks the end of a main thread frame, freeing all allocations that were
+    /// not consumed within [FrameArena::FRAMES_IN_FLIGHT] frames
+    pub fn end_frame() {
+        let mut arena = arena().lock().unwrap_or_else(|e| e.into_inner());
+        arena.frame += 1;
+
+        let frame = arena.frame;
+        arena
+            .allocations
+            .retain(|_, allocation| allocation.frame + Self::FRAMES_IN_FLIGHT > frame);
+    }
+
+    /// The number of allocations not consumed yet
+    pub fn pending() -> usize {
+        arena()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .allocations
+            .len()
+    }
+}
+
+/// Drops all handlers and pending allocations at unload
+pub(crate) fn clear_render_data() {
+    handlers()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+
+    arena()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .allocations
+        .clear();
+}
+
+pub(crate) fn render_event_and_data_func() -> ffi::UnityRenderingEventAndData {
+    Some(render_event_and_data_trampoline)
+}
+
+fn dispatch(render_thread: &RenderThread, event_id: c_int, data: *mut c_void) {
+    let handler = handlers()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&event_id)
+        .cloned();
+
+    let allocation = arena()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .allocations
+        .remove(&(data as usize));
+
+    match (handler, allocation) {
+        (Some(handler), Some(allocation)) => handler(render_thread, allocation.value.as_ref()),
+        (None, _) => log_error(&format!("No handler for render event {event_id} with data")),
+        (Some(_), None) => log_error(&format!(
+            "Render event {event_id} received data that was not allocated with FrameArena, \
+             or was already consumed"
+        )),
+    }
+}
+
+unity_api_fn! {
+    unsafe fn render_event_and_data_trampoline(event_id: c_int, data: *mut c_void) {
+        guard("render event with data", (), || {
+            let render_thread = unsafe { RenderThread::new() };
+            dispatch(&render_thread, event_id, data);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::mock::MockUnity;
+
+    static SUM: AtomicU32 = AtomicU32::new(0);
+
+    fn add(_render_thread: &RenderThread, value: &u32) {
+        SUM.fetch_add(*value, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn consumes_data_once() {
+        let _mock = MockUnity::new();
+        on_render_event_data(1, add);
+
+        let event_fn = render_event_and_data_func().unwrap();
+        let first = FrameArena::alloc(2u32);
+        let wrong_type = FrameArena::alloc("not a u32");
+
+        unsafe {
+            event_fn(1, first);
+            event_fn(1, first);
+            event_fn(1, wrong_type);
+        }
+
+        assert_eq!(SUM.load(Ordering::Relaxed), 2);
+        assert_eq!(FrameArena::pending(), 0);
+    }
+
+    #[test]
+    fn never_reuses_pointers() {
+        let _mock = MockUnity::new();
+        on_render_event_data(1, add);
+
+        let event_fn = render_event_and_data_func().unwrap();
+        let first = FrameArena::alloc(());
+        let second = FrameArena::alloc(());
+        assert_ne!(first, second);
+        assert_eq!(FrameArena::pending(), 2);
+
+        let stale = FrameArena::alloc(3u32);
+        for _ in 0..FrameArena::FRAMES_IN_FLIGHT {
+            FrameArena::end_frame();
+        }
+        let fresh = FrameArena::alloc(4u32);
+
+        let sum = SUM.load(Ordering::Relaxed);
+        unsafe { event_fn(1, stale) };
+        assert_eq!(SUM.load(Ordering::Relaxed), sum);
+        assert_ne!(stale, fresh);
+        assert_eq!(FrameArena::pending(), 1);
+    }
+
+    #[test]
+    fn frees_stale_data() {
+        let _mock = MockUnity::new();
+        FrameArena::alloc(1u8);
+        FrameArena::end_frame();
+        FrameArena::alloc(2u8);
+        assert_eq!(FrameArena::pending(), 2);
+
+        FrameArena::end_frame();
+        assert_eq!(FrameArena::pending(), 1);
+
+        FrameArena::end_frame();
+        assert_eq!(FrameArena::pending(), 0);
+    }
+}
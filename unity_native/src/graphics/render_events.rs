@@ -0,0 +1,204 @@
+use std::os::raw::c_int;
+use std::sync::Mutex;
+
+use thiserror::Error;
+
+use super::{RenderThread, UnityGraphics};
+use crate::{ContextError, context, ffi, lease};
+
+/// An error while reserving render event IDs
+#[derive(Debug, Error)]
+pub enum RenderEventErr {
+    #[error(transparent)]
+    Context(#[from] ContextError),
+
+    #[error("Unity did not provide the graphics interface")]
+    NoGraphics,
+
+    #[error("Unity does not support reserving render event IDs")]
+    Unsupported,
+
+    #[error("The plugin was unloaded")]
+    Unloaded,
+}
+
+/// An enum of render events issued from C# with `GL.IssuePluginEvent` or
+/// `CommandBuffer.IssuePluginEvent`, whose IDs are reserved through
+/// [UnityGraphics::reserve_event_ids] so they never collide with other plugins.
+///
+/// Implemented with `#[derive(RenderEvents)]` on an enum with only unit variants.
+/// The derive also exports `Get<Enum>RenderEventFunc`, which returns the function to
+/// pass to `IssuePluginEvent`, and `Get<Enum>BaseEventID`, which returns the first
+/// reserved ID, or -1 if reserving failed. C# adds the variant index to the base ID.
+///
+/// ```ignore
+/// #[derive(RenderEvents)]
+/// #[render_events(handler = on_render_event)]
+/// enum MyEvents {
+///     Upload,
+///     Draw,
+/// }
+///
+/// fn on_render_event(render_thread: &RenderThread, event: MyEvents) {}
+/// ```
+///
+/// The IDs are reserved at load when the type is listed in
+/// `#[unity_plugin(render_events(MyEvents))]`, and otherwise on first use.
+pub trait RenderEvents: Sized + 'static {
+    /// The number of events, and thereby of reserved IDs
+    const COUNT: c_int;
+
+    #[doc(hidden)]
+    fn range() -> &'static RenderEventRange;
+
+    /// The offset of this event from the base ID
+    fn to_index(&self) -> c_int;
+
+    /// The event at the given offset from the base ID
+    fn from_index(index: c_int) -> Option<Self>;
+
+    /// Called on the render thread for every event of this type
+    fn handle(self, render_thread: &RenderThread);
+
+    /// Reserves the IDs for this type, if that did not happen yet since the plugin was
+    /// loaded, and returns the base ID
+    fn reserve() -> Result<c_int, RenderEventErr> {
+        Self::range().get_or_reserve(Self::COUNT)
+    }
+
+    /// The base ID, if the IDs for this type were reserved
+    fn base_id() -> Option<c_int> {
+        Self::range().get()
+    }
+
+    /// The ID to issue this event with, if the IDs for this type were reserved
+    fn event_id(&self) -> Option<c_int> {
+        Self::base_id().map(|base| base + self.to_index())
+    }
+
+    /// Maps an ID received from Unity back to the event
+    fn from_event_id(event_id: c_int) -> Option<Self> {
+        event_id
+            .checked_sub(Self::base_id()?)
+            .and_then(Self::from_index)
+    }
+}
+
+/// The IDs reserved for a single [RenderEvents] type, along with the plugin
+/// generation they were reserved in
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct RenderEventRange {
+    reserved: Mutex<Option<(u64, c_int)>>,
+}
+
+impl Default for RenderEventRange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderEventRange {
+    pub const fn new() -> Self {
+        Self {
+            reserved: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<c_int> {
+        let reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+
+        reserved
+            .filter(|(generation, _)| *generation == lease::current())
+            .map(|(_, base)| base)
+    }
+
+    pub(crate) fn get_or_reserve(&self, count: c_int) -> Result<c_int, RenderEventErr> {
+        let mut reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = lease::current();
+
+        if let Some((reserved_generation, base)) = *reserved
+            && reserved_generation == generation
+        {
+            return Ok(base);
+        }
+
+        let graphics = context()?
+            .get::<UnityGraphics>()
+            .map_err(|_| RenderEventErr::NoGraphics)?;
+
+        let base = graphics.reserve_event_ids(count)?;
+        *reserved = Some((generation, base));
+
+        Ok(base)
+    }
+}
+
+pub(crate) fn render_event_func<E: RenderEvents>() -> ffi::UnityRenderingEvent {
+    Some(render_event_trampoline::<E>)
+}
+
+pub(crate) fn base_event_id<E: RenderEvents>() -> c_int {
+    crate::panic::guard("render event base ID", -1, || match E::reserve() {
+        Ok(base) => base,
+        Err(err) => {
+            crate::panic::log_error(&format!("Could not reserve render event IDs: {err}"));
+            -1
+        }
+    })
+}
+
+unity_api_fn! {
+    unsafe fn render_event_trampoline<E: RenderEvents>(event_id: c_int) {
+        crate::panic::guard("render event", (), || {
+            if let Some(event) = E::from_event_id(event_id) {
+                event.handle(&unsafe { RenderThread::new() });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use super::*;
+    use crate::RenderEvents;
+    use crate::mock::MockUnity;
+
+    static HANDLED: AtomicI32 = AtomicI32::new(-1);
+
+    #[derive(RenderEvents, Debug, PartialEq)]
+    #[render_events(handler = handle_test_event)]
+    enum TestEvents {
+        First,
+        Second,
+    }
+
+    fn handle_test_event(_render_thread: &RenderThread, event: TestEvents) {
+        HANDLED.store(event.to_index(), Ordering::Relaxed);
+    }
+
+    #[test]
+    fn maps_reserved_ids_to_events() {
+        let mock = MockUnity::new();
+        let graphics = mock.interfaces().get::<UnityGraphics>().unwrap();
+
+        // Taken by another plugin
+        assert_eq!(graphics.reserve_event_ids(3).unwrap(), 0);
+
+        crate::context::init(&mock.interfaces());
+
+        assert_eq!(TestEvents::base_id(), None);
+        assert_eq!(GetTestEventsBaseEventID(), 3);
+        assert_eq!(TestEvents::Second.event_id(), Some(4));
+        assert_eq!(TestEvents::from_event_id(2), None);
+        assert_eq!(TestEvents::from_event_id(5), None);
+
+        let trampoline = GetTestEventsRenderEventFunc().unwrap();
+        unsafe { trampoline(4) };
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+        crate::context::teardown();
+    }
+}
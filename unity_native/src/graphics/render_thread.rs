@@ -0,0 +1,33 @@
+use std::marker::PhantomData;
+
+/// A token proving that the code holding it runs on the Unity render thread,
+/// during a callback Unity made into the plugin. Many native graphics calls are only
+/// valid there, so wrappers for them take a `&RenderThread` argument.
+///
+/// The token is only created by the crate, inside the render callbacks it generates,
+/// and is only ever lent out for the duration of the callback. It is neither [Send]
+/// nor [Sync], so it cannot be moved or shared to another thread either:
+///
+/// ```compile_fail
+/// fn on_other_thread(render_thread: &unity_native::graphics::RenderThread) {
+///     std::thread::scope(|s| {
+///         s.spawn(|| drop(render_thread));
+///     });
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RenderThread {
+    _not_send: PhantomData<*const ()>,
+}
+
+static_assertions::assert_not_impl_any!(RenderThread: Send, Sync, Clone);
+
+impl RenderThread {
+    /// # Safety
+    /// Must only be called on the render thread, from a callback Unity invoked there
+    pub(crate) unsafe fn new() -> Self {
+        Self {
+            _not_send: PhantomData,
+        }
+    }
+}
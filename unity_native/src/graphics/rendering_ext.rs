@@ -0,0 +1,221 @@
+use std::ffi::c_void;
+use std::os::raw::{c_int, c_uint};
+
+use bitflags::bitflags;
+
+use crate::ffi;
+
+/// The parameters of a deprecated `IssuePluginCustomTextureUpdateV1` texture update
+pub type TextureUpdateParamsV1 = ffi::UnityRenderingExtTextureUpdateParamsV1;
+
+/// The parameters of an `IssuePluginCustomTextureUpdateV2` texture update
+pub type TextureUpdateParamsV2 = ffi::UnityRenderingExtTextureUpdateParamsV2;
+
+/// An event sent to the exported `UnityRenderingExtEvent` hook, with its `data`
+/// pointer decoded according to the event type. Received through
+/// [UnityPlugin::on_rendering_ext_event](crate::UnityPlugin::on_rendering_ext_event).
+#[derive(Debug)]
+pub enum RenderingExtEvent<'a> {
+    /// Issued during `SetStereoTarget`, with the current eye index
+    SetStereoTarget { eye: c_int },
+    /// Issued at the beginning of each eye's rendering loop, with the current eye index
+    SetStereoEye { eye: c_int },
+    /// Issued after stereo rendering has finished
+    StereoRenderingDone,
+    /// Issued before every draw call, with the bound shaders
+    BeforeDrawCall(&'a ffi::UnityRenderingExtBeforeDrawCallParams),
+    /// Issued after every draw call
+    AfterDrawCall,
+    /// Issued during `GrabIntoRenderTexture`. Only `source` and `destination` are set.
+    CustomGrab(&'a ffi::UnityRenderingExtCustomBlitParams),
+    /// Issued for custom blits inserted by the plugin
+    CustomBlit(&'a ffi::UnityRenderingExtCustomBlitParams),
+    /// Deprecated by Unity in favour of [RenderingExtEvent::UpdateTextureBeginV2]
+    UpdateTextureBeginV1(&'a mut TextureUpdateParamsV1),
+    /// Deprecated by Unity in favour of [RenderingExtEvent::UpdateTextureEndV2]
+    UpdateTextureEndV1(&'a mut TextureUpdateParamsV1),
+    /// Issued to update a texture. The plugin must point `texData` at the new contents.
+    UpdateTextureBeginV2(&'a mut TextureUpdateParamsV2),
+    /// Issued once Unity is done with the `texData` set during the matching begin event
+    UpdateTextureEndV2(&'a mut TextureUpdateParamsV2),
+    /// A user event, at or above `kUnityRenderingExtUserEventsStart`
+    User { event: c_uint, data: *mut c_void },
+    /// An event not known to this version of the crate, or a known event
+    /// that unexpectedly arrived without its parameters
+    Unknown { event: c_uint, data: *mut c_void },
+}
+
+impl RenderingExtEvent<'_> {
+    /// Decodes the arguments of `UnityRenderingExtEvent`
+    ///
+    /// # Safety
+    /// `data` must be the pointer Unity passed along with `event`, and stay valid
+    /// and unaliased for the lifetime of the returned value.
+    pub unsafe fn from_raw(event: ffi::UnityRenderingExtEventType, data: *mut c_void) -> Self {
+        type Event = ffi::UnityRenderingExtEventType;
+
+        let unknown = RenderingExtEvent::Unknown {
+            event: event.0,
+            data,
+        };
+
+        // The eye index is passed as the pointer value itself
+        let eye = data as isize as c_int;
+
+        unsafe {
+            match event {
+                Event::kUnityRenderingExtEventSetStereoTarget => {
+                    RenderingExtEvent::SetStereoTarget { eye }
+                }
+                Event::kUnityRenderingExtEventSetStereoEye => {
+                    RenderingExtEvent::SetStereoEye { eye }
+                }
+                Event::kUnityRenderingExtEventStereoRenderingDone => {
+                    RenderingExtEvent::StereoRenderingDone
+                }
+                Event::kUnityRenderingExtEventBeforeDrawCall => data
+                    .cast::<ffi::UnityRenderingExtBeforeDrawCallParams>()
+                    .as_ref()
+                    .map_or(unknown, RenderingExtEvent::BeforeDrawCall),
+                Event::kUnityRenderingExtEventAfterDrawCall => RenderingExtEvent::AfterDrawCall,
+                Event::kUnityRenderingExtEventCustomGrab => data
+                    .cast::<ffi::UnityRenderingExtCustomBlitParams>()
+                    .as_ref()
+                    .map_or(unknown, RenderingExtEvent::CustomGrab),
+                Event::kUnityRenderingExtEventCustomBlit => data
+                    .cast::<ffi::UnityRenderingExtCustomBlitParams>()
+                    .as_ref()
+                    .map_or(unknown, RenderingExtEvent::CustomBlit),
+                Event::kUnityRenderingExtEventUpdateTextureBeginV1 => data
+                    .cast::<TextureUpdateParamsV1>()
+                    .as_mut()
+                    .map_or(unknown, RenderingExtEvent::UpdateTextureBeginV1),
+                Event::kUnityRenderingExtEventUpdateTextureEndV1 => data
+                    .cast::<TextureUpdateParamsV1>()
+                    .as_mut()
+                    .map_or(unknown, RenderingExtEvent::UpdateTextureEndV1),
+                Event::kUnityRenderingExtEventUpdateTextureBeginV2 => data
+                    .cast::<TextureUpdateParamsV2>()
+                    .as_mut()
+                    .map_or(unknown, RenderingExtEvent::UpdateTextureBeginV2),
+                Event::kUnityRenderingExtEventUpdateTextureEndV2 => data
+                    .cast::<TextureUpdateParamsV2>()
+                    .as_mut()
+                    .map_or(unknown, RenderingExtEvent::UpdateTextureEndV2),
+                other if other.0 >= Event::kUnityRenderingExtUserEventsStart.0 => {
+                    RenderingExtEvent::User {
+                        event: other.0,
+                        data,
+                    }
+                }
+                _ => unknown,
+            }
+        }
+    }
+}
+
+bitflags! {
+    /// The queries Unity sends to the exported `UnityRenderingExtQuery` hook,
+    /// mirroring `UnityRenderingExtQueryType`. Declared with
+    /// `#[unity_plugin(rendering_ext_queries = ..)]`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct RenderingExtQuery: c_uint {
+        /// The plugin sets up the viewport rects, and Unity skips its own `SetViewport` calls
+        const OVERRIDE_VIEWPORT = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideViewport.0;
+        /// The plugin sets up the scissor rects, and Unity skips its own `SetScissor` calls
+        const OVERRIDE_SCISSOR = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideScissor.0;
+        /// The plugin renders its own VR occlusion mesh, and Unity skips its occlusion mask
+        const OVERRIDE_VR_OCCLUSION_MESH = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideVROcclussionMesh.0;
+        /// The plugin uses its own single pass stereo technique, and Unity renders the
+        /// render node graph only once
+        const OVERRIDE_VR_SINGLE_PASS = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideVRSinglePass.0;
+        /// Deprecated by Unity. Keeps the original double wide width instead of rounding
+        /// up to a power of two.
+        const KEEP_ORIGINAL_DOUBLE_WIDE_WIDTH = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryKeepOriginalDoubleWideWidth_DEPRECATED.0;
+        /// Unity sends `kUnityRenderingExtCustomBlitVRFlush` blits when the VR eye textures need flushing
+        const REQUEST_VR_FLUSH_CALLBACK = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryRequestVRFlushCallback.0;
+        /// The plugin presents the swap chain, and Unity skips its own `Present` calls
+        const OVERRIDE_PRESENT_FRAME = ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverridePresentFrame.0;
+    }
+}
+
+impl RenderingExtQuery {
+    /// Whether a plugin answering `self` handles the single query Unity sent
+    pub fn answers(self, query: ffi::UnityRenderingExtQueryType) -> bool {
+        let query = RenderingExtQuery::from_bits_retain(query.0);
+
+        !query.is_empty() && self.contains(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    type Event = ffi::UnityRenderingExtEventType;
+
+    #[test]
+    fn decodes_event_data() {
+        let eye = unsafe {
+            RenderingExtEvent::from_raw(
+                Event::kUnityRenderingExtEventSetStereoEye,
+                ptr::without_provenance_mut(1),
+            )
+        };
+        assert!(matches!(eye, RenderingExtEvent::SetStereoEye { eye: 1 }));
+
+        let mut params = TextureUpdateParamsV2 {
+            width: 64,
+            ..Default::default()
+        };
+        let update = unsafe {
+            RenderingExtEvent::from_raw(
+                Event::kUnityRenderingExtEventUpdateTextureBeginV2,
+                ptr::from_mut(&mut params).cast(),
+            )
+        };
+        match update {
+            RenderingExtEvent::UpdateTextureBeginV2(params) => assert_eq!(params.width, 64),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let missing = unsafe {
+            RenderingExtEvent::from_raw(Event::kUnityRenderingExtEventCustomBlit, ptr::null_mut())
+        };
+        assert!(matches!(
+            missing,
+            RenderingExtEvent::Unknown { event: 6, .. }
+        ));
+
+        let user = unsafe {
+            RenderingExtEvent::from_raw(
+                ffi::UnityRenderingExtEventType(Event::kUnityRenderingExtUserEventsStart.0 + 2),
+                ptr::null_mut(),
+            )
+        };
+        assert!(matches!(user, RenderingExtEvent::User { event: 13, .. }));
+    }
+
+    #[test]
+    fn answers_declared_queries() {
+        let queries =
+            RenderingExtQuery::OVERRIDE_VIEWPORT | RenderingExtQuery::OVERRIDE_PRESENT_FRAME;
+
+        assert!(
+            queries
+                .answers(ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideViewport)
+        );
+        assert!(
+            queries.answers(
+                ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverridePresentFrame
+            )
+        );
+        assert!(
+            !queries
+                .answers(ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideScissor)
+        );
+        assert!(!queries.answers(ffi::UnityRenderingExtQueryType(0)));
+    }
+}
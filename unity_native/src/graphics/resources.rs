@@ -0,0 +1,260 @@
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use super::GfxDeviceEvent;
+use crate::panic::guard;
+
+/// A native GPU resource owned by the plugin, such as a texture or buffer,
+/// that has to be rebuilt when Unity resets the graphics device.
+///
+/// Registered resources are released in reverse registration order on
+/// [GfxDeviceEvent::BeforeReset] and [GfxDeviceEvent::Shutdown], and recreated in
+/// registration order on [GfxDeviceEvent::AfterReset], or on
+/// [GfxDeviceEvent::Initialize] after a shutdown. The resource should already be
+/// created when it is registered. Resources registered while the device is released
+/// are not recreated when it comes back, because they were never released.
+/// Live resources are also released when the plugin is unloaded.
+pub trait GpuResource: Send + 'static {
+    /// Releases the native resource, because the device is about to be reset or destroyed
+    fn release(&mut self);
+
+    /// Recreates the native resource on the new or reset device
+    fn recreate(&mut self);
+}
+
+/// A registered [GpuResource]. The resource is removed from the registry
+/// once all handles to it are dropped.
+#[derive(Debug)]
+pub struct GpuResourceHandle<T> {
+    resource: Arc<Mutex<T>>,
+}
+
+impl<T> Clone for GpuResourceHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            resource: self.resource.clone(),
+        }
+    }
+}
+
+impl<T: GpuResource> GpuResourceHandle<T> {
+    /// Registers the resource, so that the crate releases and recreates it with the device
+    pub fn register(resource: T) -> Self {
+        let resource = Arc::new(Mutex::new(resource));
+        let dyn_resource: Arc<Mutex<dyn GpuResource>> = resource.clone();
+
+        let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+        registry.resources.retain(|weak| weak.strong_count() > 0);
+
+        if registry.released {
+            registry.deferred.push(Arc::downgrade(&dyn_resource));
+        } else {
+            registry.resources.push(Arc::downgrade(&dyn_resource));
+        }
+
+        Self { resource }
+    }
+
+    /// Locks the resource. Device events wait for the lock, so it should not
+    /// be held across frames.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.resource.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+struct Registry {
+    released: bool,
+    resources: Vec<Weak<Mutex<dyn GpuResource>>>,
+    /// Registered while the device was released, so they join the other
+    /// resources only after those were recreated
+    deferred: Vec<Weak<Mutex<dyn GpuResource>>>,
+}
+
+plugin_static! {
+    fn registry() -> &Mutex<Registry> = Mutex::new(Registry {
+        released: false,
+        resources: Vec::new(),
+        deferred: Vec::new(),
+    });
+}
+
+/// Releases or recreates all registered resources for the given device event
+pub(crate) fn drive_gpu_resources(event: GfxDeviceEvent) {
+    // Upgraded first, so that the hooks can register resources themselves
+    let (resources, release) = {
+        let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+
+        let release = match event {
+            GfxDeviceEvent::BeforeReset | GfxDeviceEvent::Shutdown if !registry.released => true,
+            GfxDeviceEvent::AfterReset | GfxDeviceEvent::Initialize if registry.released => false,
+            _ => return,
+        };
+
+        registry.released = release;
+        registry.resources.retain(|weak| weak.strong_count() > 0);
+
+        let resources: Vec<_> = registry
+            .resources
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+
+        if !release {
+            let deferred = std::mem::take(&mut registry.deferred);
+            registry.resources.extend(deferred);
+        }
+
+        (resources, release)
+    };
+
+    if release {
+        for resource in resources.iter().rev() {
+            let mut resource = resource.lock().unwrap_or_else(|e| e.into_inner());
+            guard("GpuResource::release", (), || resource.release());
+        }
+    } else {
+        for resource in &resources {
+            let mut resource = resource.lock().unwrap_or_else(|e| e.into_inner());
+            guard("GpuResource::recreate", (), || resource.recreate());
+        }
+    }
+}
+
+/// Releases all live resources at unload, because the device event callback
+/// is already unregistered by then
+pub(crate) fn clear_gpu_resources() {
+    let (resources, deferred) = {
+        let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+        let resources = std::mem::take(&mut registry.resources);
+        let deferred = std::mem::take(&mut registry.deferred);
+
+        if std::mem::replace(&mut registry.released, false) {
+            (Vec::new(), deferred)
+        } else {
+            (resources, deferred)
+        }
+    };
+
+    for resource in resources.iter().chain(&deferred).rev() {
+        if let Some(resource) = resource.upgrade() {
+            let mut resource = resource.lock().unwrap_or_else(|e| e.into_inner());
+            guard("GpuResource::release", (), || resource.release());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    struct TestResource {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl GpuResource for TestResource {
+        fn release(&mut self) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("release {}", self.name));
+        }
+
+        fn recreate(&mut self) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("recreate {}", self.name));
+        }
+    }
+
+    #[test]
+    fn survives_device_resets_in_order() {
+        let _mock = MockUnity::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let resource = |name| TestResource {
+            name,
+            log: log.clone(),
+        };
+
+        let _texture = GpuResourceHandle::register(resource("texture"));
+        let buffer = GpuResourceHandle::register(resource("buffer"));
+        let dropped = GpuResourceHandle::register(resource("dropped"));
+        drop(dropped);
+
+        drive_gpu_resources(GfxDeviceEvent::BeforeReset);
+        drive_gpu_resources(GfxDeviceEvent::BeforeReset);
+        drive_gpu_resources(GfxDeviceEvent::AfterReset);
+        drive_gpu_resources(GfxDeviceEvent::Shutdown);
+        drive_gpu_resources(GfxDeviceEvent::Initialize);
+
+        assert_eq!(buffer.lock().name, "buffer");
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "release buffer",
+                "release texture",
+                "recreate texture",
+                "recreate buffer",
+                "release buffer",
+                "release texture",
+                "recreate texture",
+                "recreate buffer",
+            ]
+        );
+    }
+
+    #[test]
+    fn registered_while_released_is_not_recreated() {
+        let _mock = MockUnity::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let resource = |name| TestResource {
+            name,
+            log: log.clone(),
+        };
+
+        let _texture = GpuResourceHandle::register(resource("texture"));
+        drive_gpu_resources(GfxDeviceEvent::BeforeReset);
+        let _buffer = GpuResourceHandle::register(resource("buffer"));
+        drive_gpu_resources(GfxDeviceEvent::AfterReset);
+        drive_gpu_resources(GfxDeviceEvent::BeforeReset);
+        drive_gpu_resources(GfxDeviceEvent::AfterReset);
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "release texture",
+                "recreate texture",
+                "release buffer",
+                "release texture",
+                "recreate texture",
+                "recreate buffer",
+            ]
+        );
+    }
+
+    #[test]
+    fn releases_live_resources_at_unload() {
+        let mock = MockUnity::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let resource = |name| TestResource {
+            name,
+            log: log.clone(),
+        };
+
+        let _texture = GpuResourceHandle::register(resource("texture"));
+        let _buffer = GpuResourceHandle::register(resource("buffer"));
+        drop(mock);
+
+        // Already released by the shutdown, so not released again at unload
+        let _mock = MockUnity::new();
+        let _target = GpuResourceHandle::register(resource("target"));
+        drive_gpu_resources(GfxDeviceEvent::Shutdown);
+        clear_gpu_resources();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["release buffer", "release texture", "release target"]
+        );
+    }
+}
@@ -0,0 +1,430 @@
+use std::os::raw::c_uint;
+
+use thiserror::Error;
+
+use crate::ffi;
+
+const PLAIN: u8 = 0;
+const SRGB: u8 = 1 << 0;
+const HDR: u8 = 1 << 1;
+const COMPRESSED: u8 = 1 << 2;
+const DEPTH: u8 = 1 << 3;
+const STENCIL: u8 = 1 << 4;
+
+/// The layout of a single [TextureFormat]
+struct FormatInfo {
+    channels: u8,
+    block_bytes: u8,
+    block: (u8, u8),
+    flags: u8,
+}
+
+macro_rules! texture_formats {
+    ($($name:ident = $ffi:ident => ($channels:literal, $bytes:literal, ($bw:literal, $bh:literal), $flags:expr)),* $(,)?) => {
+        /// The texture formats of the rendering extensions, mirroring `UnityRenderingExtTextureFormat`.
+        /// The variant names match the C# `GraphicsFormat` enum.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        pub enum TextureFormat {
+            $($name),*
+        }
+
+        impl TextureFormat {
+            /// Every texture format, in the order of `UnityRenderingExtTextureFormat`
+            pub const ALL: &[TextureFormat] = &[$(TextureFormat::$name),*];
+
+            fn info(self) -> FormatInfo {
+                match self {
+                    $(TextureFormat::$name => FormatInfo {
+                        channels: $channels,
+                        block_bytes: $bytes,
+                        block: ($bw, $bh),
+                        flags: $flags,
+                    }),*
+                }
+            }
+        }
+
+        impl From<TextureFormat> for ffi::UnityRenderingExtTextureFormat {
+            fn from(value: TextureFormat) -> Self {
+                match value {
+                    $(TextureFormat::$name => ffi::UnityRenderingExtTextureFormat::$ffi),*
+                }
+            }
+        }
+
+        impl TryFrom<ffi::UnityRenderingExtTextureFormat> for TextureFormat {
+            type Error = TextureFormatErr;
+
+            fn try_from(value: ffi::UnityRenderingExtTextureFormat) -> Result<Self, Self::Error> {
+                type Format = ffi::UnityRenderingExtTextureFormat;
+
+                match value {
+                    $(Format::$ffi => Ok(TextureFormat::$name),)*
+                    Format::kUnityRenderingExtFormatDepthAuto_removed_donotuse
+                    | Format::kUnityRenderingExtFormatShadowAuto_removed_donotuse
+                    | Format::kUnityRenderingExtFormatVideoAuto_removed_donotuse => {
+                        Err(TextureFormatErr::Removed(value.0))
+                    }
+                    other => Err(TextureFormatErr::Unknown(other.0)),
+                }
+            }
+        }
+    };
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TextureFormatErr {
+    #[error("Texture format {0} was removed from Unity")]
+    Removed(c_uint),
+
+    #[error("Texture format {0} is not known to this version of the crate")]
+    Unknown(c_uint),
+}
+
+// Variant = FFI constant => (channels, bytes per block, block size, flags)
+texture_formats! {
+    None = kUnityRenderingExtFormatNone => (0, 0, (1, 1), PLAIN),
+
+    // sRGB formats
+    R8_SRGB = kUnityRenderingExtFormatR8_SRGB => (1, 1, (1, 1), SRGB),
+    R8G8_SRGB = kUnityRenderingExtFormatR8G8_SRGB => (2, 2, (1, 1), SRGB),
+    R8G8B8_SRGB = kUnityRenderingExtFormatR8G8B8_SRGB => (3, 3, (1, 1), SRGB),
+    R8G8B8A8_SRGB = kUnityRenderingExtFormatR8G8B8A8_SRGB => (4, 4, (1, 1), SRGB),
+
+    // 8 bit integer formats
+    R8_UNorm = kUnityRenderingExtFormatR8_UNorm => (1, 1, (1, 1), PLAIN),
+    R8G8_UNorm = kUnityRenderingExtFormatR8G8_UNorm => (2, 2, (1, 1), PLAIN),
+    R8G8B8_UNorm = kUnityRenderingExtFormatR8G8B8_UNorm => (3, 3, (1, 1), PLAIN),
+    R8G8B8A8_UNorm = kUnityRenderingExtFormatR8G8B8A8_UNorm => (4, 4, (1, 1), PLAIN),
+    R8_SNorm = kUnityRenderingExtFormatR8_SNorm => (1, 1, (1, 1), PLAIN),
+    R8G8_SNorm = kUnityRenderingExtFormatR8G8_SNorm => (2, 2, (1, 1), PLAIN),
+    R8G8B8_SNorm = kUnityRenderingExtFormatR8G8B8_SNorm => (3, 3, (1, 1), PLAIN),
+    R8G8B8A8_SNorm = kUnityRenderingExtFormatR8G8B8A8_SNorm => (4, 4, (1, 1), PLAIN),
+    R8_UInt = kUnityRenderingExtFormatR8_UInt => (1, 1, (1, 1), PLAIN),
+    R8G8_UInt = kUnityRenderingExtFormatR8G8_UInt => (2, 2, (1, 1), PLAIN),
+    R8G8B8_UInt = kUnityRenderingExtFormatR8G8B8_UInt => (3, 3, (1, 1), PLAIN),
+    R8G8B8A8_UInt = kUnityRenderingExtFormatR8G8B8A8_UInt => (4, 4, (1, 1), PLAIN),
+    R8_SInt = kUnityRenderingExtFormatR8_SInt => (1, 1, (1, 1), PLAIN),
+    R8G8_SInt = kUnityRenderingExtFormatR8G8_SInt => (2, 2, (1, 1), PLAIN),
+    R8G8B8_SInt = kUnityRenderingExtFormatR8G8B8_SInt => (3, 3, (1, 1), PLAIN),
+    R8G8B8A8_SInt = kUnityRenderingExtFormatR8G8B8A8_SInt => (4, 4, (1, 1), PLAIN),
+
+    // 16 bit integer formats
+    R16_UNorm = kUnityRenderingExtFormatR16_UNorm => (1, 2, (1, 1), PLAIN),
+    R16G16_UNorm = kUnityRenderingExtFormatR16G16_UNorm => (2, 4, (1, 1), PLAIN),
+    R16G16B16_UNorm = kUnityRenderingExtFormatR16G16B16_UNorm => (3, 6, (1, 1), PLAIN),
+    R16G16B16A16_UNorm = kUnityRenderingExtFormatR16G16B16A16_UNorm => (4, 8, (1, 1), PLAIN),
+    R16_SNorm = kUnityRenderingExtFormatR16_SNorm => (1, 2, (1, 1), PLAIN),
+    R16G16_SNorm = kUnityRenderingExtFormatR16G16_SNorm => (2, 4, (1, 1), PLAIN),
+    R16G16B16_SNorm = kUnityRenderingExtFormatR16G16B16_SNorm => (3, 6, (1, 1), PLAIN),
+    R16G16B16A16_SNorm = kUnityRenderingExtFormatR16G16B16A16_SNorm => (4, 8, (1, 1), PLAIN),
+    R16_UInt = kUnityRenderingExtFormatR16_UInt => (1, 2, (1, 1), PLAIN),
+    R16G16_UInt = kUnityRenderingExtFormatR16G16_UInt => (2, 4, (1, 1), PLAIN),
+    R16G16B16_UInt = kUnityRenderingExtFormatR16G16B16_UInt => (3, 6, (1, 1), PLAIN),
+    R16G16B16A16_UInt = kUnityRenderingExtFormatR16G16B16A16_UInt => (4, 8, (1, 1), PLAIN),
+    R16_SInt = kUnityRenderingExtFormatR16_SInt => (1, 2, (1, 1), PLAIN),
+    R16G16_SInt = kUnityRenderingExtFormatR16G16_SInt => (2, 4, (1, 1), PLAIN),
+    R16G16B16_SInt = kUnityRenderingExtFormatR16G16B16_SInt => (3, 6, (1, 1), PLAIN),
+    R16G16B16A16_SInt = kUnityRenderingExtFormatR16G16B16A16_SInt => (4, 8, (1, 1), PLAIN),
+
+    // 32 bit integer formats
+    R32_UInt = kUnityRenderingExtFormatR32_UInt => (1, 4, (1, 1), PLAIN),
+    R32G32_UInt = kUnityRenderingExtFormatR32G32_UInt => (2, 8, (1, 1), PLAIN),
+    R32G32B32_UInt = kUnityRenderingExtFormatR32G32B32_UInt => (3, 12, (1, 1), PLAIN),
+    R32G32B32A32_UInt = kUnityRenderingExtFormatR32G32B32A32_UInt => (4, 16, (1, 1), PLAIN),
+    R32_SInt = kUnityRenderingExtFormatR32_SInt => (1, 4, (1, 1), PLAIN),
+    R32G32_SInt = kUnityRenderingExtFormatR32G32_SInt => (2, 8, (1, 1), PLAIN),
+    R32G32B32_SInt = kUnityRenderingExtFormatR32G32B32_SInt => (3, 12, (1, 1), PLAIN),
+    R32G32B32A32_SInt = kUnityRenderingExtFormatR32G32B32A32_SInt => (4, 16, (1, 1), PLAIN),
+
+    // HDR formats
+    R16_SFloat = kUnityRenderingExtFormatR16_SFloat => (1, 2, (1, 1), HDR),
+    R16G16_SFloat = kUnityRenderingExtFormatR16G16_SFloat => (2, 4, (1, 1), HDR),
+    R16G16B16_SFloat = kUnityRenderingExtFormatR16G16B16_SFloat => (3, 6, (1, 1), HDR),
+    R16G16B16A16_SFloat = kUnityRenderingExtFormatR16G16B16A16_SFloat => (4, 8, (1, 1), HDR),
+    R32_SFloat = kUnityRenderingExtFormatR32_SFloat => (1, 4, (1, 1), HDR),
+    R32G32_SFloat = kUnityRenderingExtFormatR32G32_SFloat => (2, 8, (1, 1), HDR),
+    R32G32B32_SFloat = kUnityRenderingExtFormatR32G32B32_SFloat => (3, 12, (1, 1), HDR),
+    R32G32B32A32_SFloat = kUnityRenderingExtFormatR32G32B32A32_SFloat => (4, 16, (1, 1), HDR),
+
+    // Luminance and alpha formats
+    L8_UNorm = kUnityRenderingExtFormatL8_UNorm => (1, 1, (1, 1), PLAIN),
+    A8_UNorm = kUnityRenderingExtFormatA8_UNorm => (1, 1, (1, 1), PLAIN),
+    A16_UNorm = kUnityRenderingExtFormatA16_UNorm => (1, 2, (1, 1), PLAIN),
+
+    // BGR formats
+    B8G8R8_SRGB = kUnityRenderingExtFormatB8G8R8_SRGB => (3, 3, (1, 1), SRGB),
+    B8G8R8A8_SRGB = kUnityRenderingExtFormatB8G8R8A8_SRGB => (4, 4, (1, 1), SRGB),
+    B8G8R8_UNorm = kUnityRenderingExtFormatB8G8R8_UNorm => (3, 3, (1, 1), PLAIN),
+    B8G8R8A8_UNorm = kUnityRenderingExtFormatB8G8R8A8_UNorm => (4, 4, (1, 1), PLAIN),
+    B8G8R8_SNorm = kUnityRenderingExtFormatB8G8R8_SNorm => (3, 3, (1, 1), PLAIN),
+    B8G8R8A8_SNorm = kUnityRenderingExtFormatB8G8R8A8_SNorm => (4, 4, (1, 1), PLAIN),
+    B8G8R8_UInt = kUnityRenderingExtFormatB8G8R8_UInt => (3, 3, (1, 1), PLAIN),
+    B8G8R8A8_UInt = kUnityRenderingExtFormatB8G8R8A8_UInt => (4, 4, (1, 1), PLAIN),
+    B8G8R8_SInt = kUnityRenderingExtFormatB8G8R8_SInt => (3, 3, (1, 1), PLAIN),
+    B8G8R8A8_SInt = kUnityRenderingExtFormatB8G8R8A8_SInt => (4, 4, (1, 1), PLAIN),
+
+    // 16 bit packed formats
+    R4G4B4A4_UNormPack16 = kUnityRenderingExtFormatR4G4B4A4_UNormPack16 => (4, 2, (1, 1), PLAIN),
+    B4G4R4A4_UNormPack16 = kUnityRenderingExtFormatB4G4R4A4_UNormPack16 => (4, 2, (1, 1), PLAIN),
+    R5G6B5_UNormPack16 = kUnityRenderingExtFormatR5G6B5_UNormPack16 => (3, 2, (1, 1), PLAIN),
+    B5G6R5_UNormPack16 = kUnityRenderingExtFormatB5G6R5_UNormPack16 => (3, 2, (1, 1), PLAIN),
+    R5G5B5A1_UNormPack16 = kUnityRenderingExtFormatR5G5B5A1_UNormPack16 => (4, 2, (1, 1), PLAIN),
+    B5G5R5A1_UNormPack16 = kUnityRenderingExtFormatB5G5R5A1_UNormPack16 => (4, 2, (1, 1), PLAIN),
+    A1R5G5B5_UNormPack16 = kUnityRenderingExtFormatA1R5G5B5_UNormPack16 => (4, 2, (1, 1), PLAIN),
+
+    // 32 bit packed formats. The A10R10G10B10 extended range formats take 64 bits
+    E5B9G9R9_UFloatPack32 = kUnityRenderingExtFormatE5B9G9R9_UFloatPack32 => (3, 4, (1, 1), HDR),
+    B10G11R11_UFloatPack32 = kUnityRenderingExtFormatB10G11R11_UFloatPack32 => (3, 4, (1, 1), HDR),
+    A2B10G10R10_UNormPack32 = kUnityRenderingExtFormatA2B10G10R10_UNormPack32 => (4, 4, (1, 1), PLAIN),
+    A2B10G10R10_UIntPack32 = kUnityRenderingExtFormatA2B10G10R10_UIntPack32 => (4, 4, (1, 1), PLAIN),
+    A2B10G10R10_SIntPack32 = kUnityRenderingExtFormatA2B10G10R10_SIntPack32 => (4, 4, (1, 1), PLAIN),
+    A2R10G10B10_UNormPack32 = kUnityRenderingExtFormatA2R10G10B10_UNormPack32 => (4, 4, (1, 1), PLAIN),
+    A2R10G10B10_UIntPack32 = kUnityRenderingExtFormatA2R10G10B10_UIntPack32 => (4, 4, (1, 1), PLAIN),
+    A2R10G10B10_SIntPack32 = kUnityRenderingExtFormatA2R10G10B10_SIntPack32 => (4, 4, (1, 1), PLAIN),
+    A2R10G10B10_XRSRGBPack32 = kUnityRenderingExtFormatA2R10G10B10_XRSRGBPack32 => (4, 4, (1, 1), SRGB),
+    A2R10G10B10_XRUNormPack32 = kUnityRenderingExtFormatA2R10G10B10_XRUNormPack32 => (4, 4, (1, 1), PLAIN),
+    R10G10B10_XRSRGBPack32 = kUnityRenderingExtFormatR10G10B10_XRSRGBPack32 => (3, 4, (1, 1), SRGB),
+    R10G10B10_XRUNormPack32 = kUnityRenderingExtFormatR10G10B10_XRUNormPack32 => (3, 4, (1, 1), PLAIN),
+    A10R10G10B10_XRSRGBPack32 = kUnityRenderingExtFormatA10R10G10B10_XRSRGBPack32 => (4, 8, (1, 1), SRGB),
+    A10R10G10B10_XRUNormPack32 = kUnityRenderingExtFormatA10R10G10B10_XRUNormPack32 => (4, 8, (1, 1), PLAIN),
+
+    // Legacy ARGB formats
+    A8R8G8B8_SRGB = kUnityRenderingExtFormatA8R8G8B8_SRGB => (4, 4, (1, 1), SRGB),
+    A8R8G8B8_UNorm = kUnityRenderingExtFormatA8R8G8B8_UNorm => (4, 4, (1, 1), PLAIN),
+    A32R32G32B32_SFloat = kUnityRenderingExtFormatA32R32G32B32_SFloat => (4, 16, (1, 1), HDR),
+
+    // Depth and stencil formats
+    D16_UNorm = kUnityRenderingExtFormatD16_UNorm => (1, 2, (1, 1), DEPTH),
+    D24_UNorm = kUnityRenderingExtFormatD24_UNorm => (1, 4, (1, 1), DEPTH),
+    D24_UNorm_S8_UInt = kUnityRenderingExtFormatD24_UNorm_S8_UInt => (2, 4, (1, 1), DEPTH | STENCIL),
+    D32_SFloat = kUnityRenderingExtFormatD32_SFloat => (1, 4, (1, 1), DEPTH),
+    D32_SFloat_S8_UInt = kUnityRenderingExtFormatD32_SFloat_S8_UInt => (2, 8, (1, 1), DEPTH | STENCIL),
+    S8_UInt = kUnityRenderingExtFormatS8_UInt => (1, 1, (1, 1), STENCIL),
+
+    // Compressed formats
+    RGBA_DXT1_SRGB = kUnityRenderingExtFormatRGBA_DXT1_SRGB => (4, 8, (4, 4), COMPRESSED | SRGB),
+    RGBA_DXT1_UNorm = kUnityRenderingExtFormatRGBA_DXT1_UNorm => (4, 8, (4, 4), COMPRESSED),
+    RGBA_DXT3_SRGB = kUnityRenderingExtFormatRGBA_DXT3_SRGB => (4, 16, (4, 4), COMPRESSED | SRGB),
+    RGBA_DXT3_UNorm = kUnityRenderingExtFormatRGBA_DXT3_UNorm => (4, 16, (4, 4), COMPRESSED),
+    RGBA_DXT5_SRGB = kUnityRenderingExtFormatRGBA_DXT5_SRGB => (4, 16, (4, 4), COMPRESSED | SRGB),
+    RGBA_DXT5_UNorm = kUnityRenderingExtFormatRGBA_DXT5_UNorm => (4, 16, (4, 4), COMPRESSED),
+    R_BC4_UNorm = kUnityRenderingExtFormatR_BC4_UNorm => (1, 8, (4, 4), COMPRESSED),
+    R_BC4_SNorm = kUnityRenderingExtFormatR_BC4_SNorm => (1, 8, (4, 4), COMPRESSED),
+    RG_BC5_UNorm = kUnityRenderingExtFormatRG_BC5_UNorm => (2, 16, (4, 4), COMPRESSED),
+    RG_BC5_SNorm = kUnityRenderingExtFormatRG_BC5_SNorm => (2, 16, (4, 4), COMPRESSED),
+    RGB_BC6H_UFloat = kUnityRenderingExtFormatRGB_BC6H_UFloat => (3, 16, (4, 4), COMPRESSED | HDR),
+    RGB_BC6H_SFloat = kUnityRenderingExtFormatRGB_BC6H_SFloat => (3, 16, (4, 4), COMPRESSED | HDR),
+    RGBA_BC7_SRGB = kUnityRenderingExtFormatRGBA_BC7_SRGB => (4, 16, (4, 4), COMPRESSED | SRGB),
+    RGBA_BC7_UNorm = kUnityRenderingExtFormatRGBA_BC7_UNorm => (4, 16, (4, 4), COMPRESSED),
+    RGB_PVRTC_2Bpp_SRGB = kUnityRenderingExtFormatRGB_PVRTC_2Bpp_SRGB => (3, 8, (8, 4), COMPRESSED | SRGB),
+    RGB_PVRTC_2Bpp_UNorm = kUnityRenderingExtFormatRGB_PVRTC_2Bpp_UNorm => (3, 8, (8, 4), COMPRESSED),
+    RGB_PVRTC_4Bpp_SRGB = kUnityRenderingExtFormatRGB_PVRTC_4Bpp_SRGB => (3, 8, (4, 4), COMPRESSED | SRGB),
+    RGB_PVRTC_4Bpp_UNorm = kUnityRenderingExtFormatRGB_PVRTC_4Bpp_UNorm => (3, 8, (4, 4), COMPRESSED),
+    RGBA_PVRTC_2Bpp_SRGB = kUnityRenderingExtFormatRGBA_PVRTC_2Bpp_SRGB => (4, 8, (8, 4), COMPRESSED | SRGB),
+    RGBA_PVRTC_2Bpp_UNorm = kUnityRenderingExtFormatRGBA_PVRTC_2Bpp_UNorm => (4, 8, (8, 4), COMPRESSED),
+    RGBA_PVRTC_4Bpp_SRGB = kUnityRenderingExtFormatRGBA_PVRTC_4Bpp_SRGB => (4, 8, (4, 4), COMPRESSED | SRGB),
+    RGBA_PVRTC_4Bpp_UNorm = kUnityRenderingExtFormatRGBA_PVRTC_4Bpp_UNorm => (4, 8, (4, 4), COMPRESSED),
+    RGB_ETC_UNorm = kUnityRenderingExtFormatRGB_ETC_UNorm => (3, 8, (4, 4), COMPRESSED),
+    RGB_ETC2_SRGB = kUnityRenderingExtFormatRGB_ETC2_SRGB => (3, 8, (4, 4), COMPRESSED | SRGB),
+    RGB_ETC2_UNorm = kUnityRenderingExtFormatRGB_ETC2_UNorm => (3, 8, (4, 4), COMPRESSED),
+    RGB_A1_ETC2_SRGB = kUnityRenderingExtFormatRGB_A1_ETC2_SRGB => (4, 8, (4, 4), COMPRESSED | SRGB),
+    RGB_A1_ETC2_UNorm = kUnityRenderingExtFormatRGB_A1_ETC2_UNorm => (4, 8, (4, 4), COMPRESSED),
+    RGBA_ETC2_SRGB = kUnityRenderingExtFormatRGBA_ETC2_SRGB => (4, 16, (4, 4), COMPRESSED | SRGB),
+    RGBA_ETC2_UNorm = kUnityRenderingExtFormatRGBA_ETC2_UNorm => (4, 16, (4, 4), COMPRESSED),
+    R_EAC_UNorm = kUnityRenderingExtFormatR_EAC_UNorm => (1, 8, (4, 4), COMPRESSED),
+    R_EAC_SNorm = kUnityRenderingExtFormatR_EAC_SNorm => (1, 8, (4, 4), COMPRESSED),
+    RG_EAC_UNorm = kUnityRenderingExtFormatRG_EAC_UNorm => (2, 16, (4, 4), COMPRESSED),
+    RG_EAC_SNorm = kUnityRenderingExtFormatRG_EAC_SNorm => (2, 16, (4, 4), COMPRESSED),
+    RGBA_ASTC4X4_SRGB = kUnityRenderingExtFormatRGBA_ASTC4X4_SRGB => (4, 16, (4, 4), COMPRESSED | SRGB),
+    RGBA_ASTC4X4_UNorm = kUnityRenderingExtFormatRGBA_ASTC4X4_UNorm => (4, 16, (4, 4), COMPRESSED),
+    RGBA_ASTC5X5_SRGB = kUnityRenderingExtFormatRGBA_ASTC5X5_SRGB => (4, 16, (5, 5), COMPRESSED | SRGB),
+    RGBA_ASTC5X5_UNorm = kUnityRenderingExtFormatRGBA_ASTC5X5_UNorm => (4, 16, (5, 5), COMPRESSED),
+    RGBA_ASTC6X6_SRGB = kUnityRenderingExtFormatRGBA_ASTC6X6_SRGB => (4, 16, (6, 6), COMPRESSED | SRGB),
+    RGBA_ASTC6X6_UNorm = kUnityRenderingExtFormatRGBA_ASTC6X6_UNorm => (4, 16, (6, 6), COMPRESSED),
+    RGBA_ASTC8X8_SRGB = kUnityRenderingExtFormatRGBA_ASTC8X8_SRGB => (4, 16, (8, 8), COMPRESSED | SRGB),
+    RGBA_ASTC8X8_UNorm = kUnityRenderingExtFormatRGBA_ASTC8X8_UNorm => (4, 16, (8, 8), COMPRESSED),
+    RGBA_ASTC10X10_SRGB = kUnityRenderingExtFormatRGBA_ASTC10X10_SRGB => (4, 16, (10, 10), COMPRESSED | SRGB),
+    RGBA_ASTC10X10_UNorm = kUnityRenderingExtFormatRGBA_ASTC10X10_UNorm => (4, 16, (10, 10), COMPRESSED),
+    RGBA_ASTC12X12_SRGB = kUnityRenderingExtFormatRGBA_ASTC12X12_SRGB => (4, 16, (12, 12), COMPRESSED | SRGB),
+    RGBA_ASTC12X12_UNorm = kUnityRenderingExtFormatRGBA_ASTC12X12_UNorm => (4, 16, (12, 12), COMPRESSED),
+
+    // Video formats
+    YUV2 = kUnityRenderingExtFormatYUV2 => (3, 4, (2, 1), PLAIN),
+
+    // ASTC HDR formats
+    RGBA_ASTC4X4_UFloat = kUnityRenderingExtFormatRGBA_ASTC4X4_UFloat => (4, 16, (4, 4), COMPRESSED | HDR),
+    RGBA_ASTC5X5_UFloat = kUnityRenderingExtFormatRGBA_ASTC5X5_UFloat => (4, 16, (5, 5), COMPRESSED | HDR),
+    RGBA_ASTC6X6_UFloat = kUnityRenderingExtFormatRGBA_ASTC6X6_UFloat => (4, 16, (6, 6), COMPRESSED | HDR),
+    RGBA_ASTC8X8_UFloat = kUnityRenderingExtFormatRGBA_ASTC8X8_UFloat => (4, 16, (8, 8), COMPRESSED | HDR),
+    RGBA_ASTC10X10_UFloat = kUnityRenderingExtFormatRGBA_ASTC10X10_UFloat => (4, 16, (10, 10), COMPRESSED | HDR),
+    RGBA_ASTC12X12_UFloat = kUnityRenderingExtFormatRGBA_ASTC12X12_UFloat => (4, 16, (12, 12), COMPRESSED | HDR),
+}
+
+impl TextureFormat {
+    /// The number of color, depth or stencil components
+    pub fn channels(self) -> u32 {
+        self.info().channels.into()
+    }
+
+    /// The size in bytes of a single block of [TextureFormat::block_size] pixels.
+    /// This is the size of a single pixel for uncompressed formats.
+    pub fn bytes_per_block(self) -> u32 {
+        self.info().block_bytes.into()
+    }
+
+    /// The size in bytes of a single pixel, or [None] for formats that store pixels in blocks
+    pub fn bytes_per_pixel(self) -> Option<u32> {
+        (self.block_size() == (1, 1)).then(|| self.bytes_per_block())
+    }
+
+    /// The width and height in pixels of a block, `(1, 1)` for uncompressed formats
+    pub fn block_size(self) -> (u32, u32) {
+        let (width, height) = self.info().block;
+        (width.into(), height.into())
+    }
+
+    pub fn is_compressed(self) -> bool {
+        self.info().flags & COMPRESSED != 0
+    }
+
+    pub fn is_depth(self) -> bool {
+        self.info().flags & DEPTH != 0
+    }
+
+    pub fn is_stencil(self) -> bool {
+        self.info().flags & STENCIL != 0
+    }
+
+    /// Whether the color channels are stored sRGB encoded
+    pub fn is_srgb(self) -> bool {
+        self.info().flags & SRGB != 0
+    }
+
+    /// Whether the format stores floating point values beyond the 0 to 1 range
+    pub fn is_hdr(self) -> bool {
+        self.info().flags & HDR != 0
+    }
+
+    /// The number of mip levels of a full mip chain, down to 1x1
+    pub fn full_mip_count(width: u32, height: u32) -> u32 {
+        u32::BITS - width.max(height).max(1).leading_zeros()
+    }
+
+    /// The size in bytes of a single mip level, or [None] if it does not fit in a `usize`
+    pub fn level_size(self, width: u32, height: u32) -> Option<usize> {
+        let (block_width, block_height) = self.block_size();
+
+        let mut blocks_x = width.div_ceil(block_width) as usize;
+        let mut blocks_y = height.div_ceil(block_height) as usize;
+
+        // PVRTC decodes every block from its neighbours, and needs at least 2x2 of them
+        if self.is_pvrtc() {
+            blocks_x = blocks_x.max(2);
+            blocks_y = blocks_y.max(2);
+        }
+
+        blocks_x
+            .checked_mul(blocks_y)?
+            .checked_mul(self.bytes_per_block() as usize)
+    }
+
+    /// The total size in bytes of the first `mip_count` mip levels of a texture, with
+    /// the base level being `width` by `height`. Clamped to [TextureFormat::full_mip_count].
+    /// Returns [None] if the size does not fit in a `usize`.
+    pub fn mip_chain_size(self, width: u32, height: u32, mip_count: u32) -> Option<usize> {
+        (0..mip_count.min(Self::full_mip_count(width, height))).try_fold(0usize, |total, level| {
+            let level_size = self.level_size((width >> level).max(1), (height >> level).max(1))?;
+            total.checked_add(level_size)
+        })
+    }
+
+    fn is_pvrtc(self) -> bool {
+        matches!(
+            self,
+            TextureFormat::RGB_PVRTC_2Bpp_SRGB
+                | TextureFormat::RGB_PVRTC_2Bpp_UNorm
+                | TextureFormat::RGB_PVRTC_4Bpp_SRGB
+                | TextureFormat::RGB_PVRTC_4Bpp_UNorm
+                | TextureFormat::RGBA_PVRTC_2Bpp_SRGB
+                | TextureFormat::RGBA_PVRTC_2Bpp_UNorm
+                | TextureFormat::RGBA_PVRTC_4Bpp_SRGB
+                | TextureFormat::RGBA_PVRTC_4Bpp_UNorm
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Format = ffi::UnityRenderingExtTextureFormat;
+
+    #[test]
+    fn round_trips_with_ffi() {
+        for &format in TextureFormat::ALL {
+            let raw = ffi::UnityRenderingExtTextureFormat::from(format);
+            assert_eq!(TextureFormat::try_from(raw), Ok(format));
+        }
+
+        // Every value up to the last format, except the removed ones
+        let last = Format::kUnityRenderingExtFormatLast.0;
+        assert_eq!(TextureFormat::ALL.len(), last as usize + 1 - 3);
+
+        assert_eq!(
+            TextureFormat::try_from(Format::kUnityRenderingExtFormatShadowAuto_removed_donotuse),
+            Err(TextureFormatErr::Removed(
+                Format::kUnityRenderingExtFormatShadowAuto_removed_donotuse.0
+            ))
+        );
+        assert_eq!(
+            TextureFormat::try_from(ffi::UnityRenderingExtTextureFormat(last + 1)),
+            Err(TextureFormatErr::Unknown(last + 1))
+        );
+    }
+
+    #[test]
+    fn computes_sizes() {
+        assert_eq!(TextureFormat::R8G8B8A8_SRGB.bytes_per_pixel(), Some(4));
+        assert!(TextureFormat::R8G8B8A8_SRGB.is_srgb());
+        assert!(TextureFormat::D24_UNorm_S8_UInt.is_stencil());
+        assert!(TextureFormat::RGB_BC6H_UFloat.is_hdr());
+        assert_eq!(TextureFormat::RGBA_BC7_UNorm.bytes_per_pixel(), None);
+
+        assert_eq!(TextureFormat::full_mip_count(256, 64), 9);
+
+        // 256x256 RGBA8, all 9 levels
+        assert_eq!(
+            TextureFormat::R8G8B8A8_UNorm.mip_chain_size(256, 256, 16),
+            Some(
+                (0..9)
+                    .map(|level| (256usize >> level).pow(2) * 4)
+                    .sum::<usize>()
+            )
+        );
+
+        // 16x16, 8x8, 4x4, 2x2, 1x1 in 4x4 blocks of 8 bytes
+        assert_eq!(
+            TextureFormat::RGBA_DXT1_UNorm.mip_chain_size(16, 16, 5),
+            Some((16 + 4 + 1 + 1 + 1) * 8)
+        );
+
+        assert_eq!(
+            TextureFormat::RGBA_ASTC6X6_UNorm.level_size(13, 7),
+            Some(3 * 2 * 16)
+        );
+        assert_eq!(
+            TextureFormat::RGB_PVRTC_4Bpp_UNorm.level_size(4, 4),
+            Some(4 * 8)
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_sizes() {
+        let format = TextureFormat::R32G32B32A32_SFloat;
+
+        assert_eq!(format.level_size(u32::MAX, u32::MAX), None);
+        assert_eq!(format.mip_chain_size(u32::MAX, u32::MAX, 32), None);
+    }
+}
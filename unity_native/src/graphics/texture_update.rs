@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::os::raw::c_uint;
+use std::ptr;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+
+use super::{RenderThread, RenderingExtEvent, TextureFormat, TextureUpdateParamsV2};
+use crate::ffi;
+use crate::panic::log_error;
+
+type Producer = Arc<dyn Fn(&RenderThread, TextureUpdate<'_>) + Send + Sync>;
+
+/// A texture update requested through `CommandBuffer.IssuePluginCustomTextureUpdateV2`,
+/// passed to the producer registered with [TextureUpdater::register]
+#[derive(Debug)]
+pub struct TextureUpdate<'a> {
+    /// The native texture ID of the texture being updated
+    pub texture_id: isize,
+    /// The `userData` the update was issued with
+    pub user_data: c_uint,
+    pub format: ffi::UnityRenderingExtTextureFormat,
+    pub width: c_uint,
+    pub height: c_uint,
+    /// The bytes per pixel of the texture
+    pub bpp: c_uint,
+    /// The new texture contents in the layout of `format`, sized by
+    /// [TextureFormat::level_size], or `width * height * bpp` bytes for unknown formats.
+    /// Reused across frames, so it still holds the contents of an earlier update.
+    pub data: &'a mut [u8],
+}
+
+struct Updater {
+    producers: HashMap<c_uint, Producer>,
+    /// Buffers handed to Unity, keyed on their address, until the matching end event
+    in_flight: HashMap<usize, Vec<u8>>,
+    pool: Vec<Vec<u8>>,
+}
+
+plugin_static! {
+    fn updater() -> &LazyLock<Mutex<Updater>> = LazyLock::new(|| {
+        Mutex::new(Updater {
+            producers: HashMap::new(),
+            in_flight: HashMap::new(),
+            pool: Vec::new(),
+        })
+    });
+}
+
+fn lock() -> MutexGuard<'static, Updater> {
+    updater().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Streams texture contents from the CPU through `IssuePluginCustomTextureUpdateV2`.
+///
+/// When Unity sends [RenderingExtEvent::UpdateTextureBeginV2], the crate allocates a
+/// buffer of the right size and calls the producer registered for the `userData` of
+/// the update to fill it. The buffer is returned to a pool on the matching
+/// [RenderingExtEvent::UpdateTextureEndV2], and reused by later updates.
+/// Handled updates are not passed on to
+/// [UnityPlugin::on_rendering_ext_event](crate::UnityPlugin::on_rendering_ext_event).
+pub struct TextureUpdater;
+
+impl TextureUpdater {
+    /// The maximum number of unused buffers kept for later updates
+    pub const MAX_POOLED: usize = 16;
+
+    /// Registers the producer for updates issued with `user_data`, replacing any
+    /// producer previously registered for it. The producer runs on the render thread.
+    pub fn register<F>(user_data: c_uint, producer: F)
+    where
+        F: Fn(&RenderThread, TextureUpdate<'_>) + Send + Sync + 'static,
+    {
+        lock().producers.insert(user_data, Arc::new(producer));
+    }
+
+    /// Removes the producer for `user_data`, returning whether there was one
+    pub fn unregister(user_data: c_uint) -> bool {
+        lock().producers.remove(&user_data).is_some()
+    }
+
+    /// The number of buffers waiting in the pool
+    pub fn pooled() -> usize {
+        lock().pool.len()
+    }
+
+    /// The number of buffers Unity has not released yet
+    pub fn in_flight() -> usize {
+        lock().in_flight.len()
+    }
+}
+
+/// Handles the texture update events of registered producers, returning
+/// whether the event was handled
+pub(crate) fn handle_texture_update(
+    render_thread: &RenderThread,
+    event: &mut RenderingExtEvent,
+) -> bool {
+    match event {
+        RenderingExtEvent::UpdateTextureBeginV2(params) => begin_update(render_thread, params),
+        RenderingExtEvent::UpdateTextureEndV2(params) => end_update(params),
+        _ => false,
+    }
+}
+
+fn begin_update(render_thread: &RenderThread, params: &mut TextureUpdateParamsV2) -> bool {
+    let size = texture_size(params);
+
+    // Every empty buffer has the same dangling address, which keys the buffers in flight
+    if params.width == 0 || params.height == 0 || size == Some(0) {
+        return false;
+    }
+
+    // Not locked while producing, so that producers can (un)register others
+    let Some(producer) = lock().producers.get(&params.userData).cloned() else {
+        return false;
+    };
+
+    let Some(size) = size else {
+        log_error(&format!(
+            "Texture update {}x{} with {} bytes per pixel is too large",
+            params.width, params.height, params.bpp
+        ));
+        return false;
+    };
+
+    let mut buffer = take_buffer(&mut lock().pool, size);
+
+    producer(
+        render_thread,
+        TextureUpdate {
+            texture_id: params.textureID as isize,
+            user_data: params.userData,
+            format: params.format,
+            width: params.width,
+            height: params.height,
+            bpp: params.bpp,
+            data: &mut buffer,
+        },
+    );
+
+    params.texData = buffer.as_mut_ptr().cast();
+    lock().in_flight.insert(params.texData as usize, buffer);
+
+    true
+}
+
+fn end_update(params: &mut TextureUpdateParamsV2) -> bool {
+    let mut updater = lock();
+
+    let Some(buffer) = updater.in_flight.remove(&(params.texData as usize)) else {
+        return false;
+    };
+
+    if updater.pool.len() < TextureUpdater::MAX_POOLED {
+        updater.pool.push(buffer);
+    }
+
+    params.texData = ptr::null_mut();
+
+    true
+}
+
+/// The buffer size of the update, or [None] if it does not fit in a `usize`,
+/// which large textures can exceed on 32-bit targets
+fn texture_size(params: &TextureUpdateParamsV2) -> Option<usize> {
+    match TextureFormat::try_from(params.format) {
+        Ok(TextureFormat::None) | Err(_) => {}
+        Ok(format) => return format.level_size(params.width, params.height),
+    }
+
+    (params.width as usize)
+        .checked_mul(params.height as usize)?
+        .checked_mul(params.bpp as usize)
+}
+
+/// Takes the smallest pooled buffer that fits, or allocates a new one
+fn take_buffer(pool: &mut Vec<Vec<u8>>, size: usize) -> Vec<u8> {
+    let best = pool
+        .iter()
+        .enumerate()
+        .filter(|(_, buffer)| buffer.capacity() >= size)
+        .min_by_key(|(_, buffer)| buffer.capacity())
+        .map(|(index, _)| index);
+
+    let mut buffer = match best {
+        Some(index) => pool.swap_remove(index),
+        None => Vec::with_capacity(size),
+    };
+
+    buffer.resize(size, 0);
+    buffer
+}
+
+/// Drops all producers and buffers at unload
+pub(crate) fn clear_texture_updates() {
+    let mut updater = lock();
+
+    updater.producers.clear();
+    updater.in_flight.clear();
+    updater.pool.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    fn params(user_data: c_uint) -> TextureUpdateParamsV2 {
+        TextureUpdateParamsV2 {
+            userData: user_data,
+            width: 4,
+            height: 2,
+            bpp: 4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn streams_through_pooled_buffers() {
+        let _mock = MockUnity::new();
+        let render_thread = unsafe { RenderThread::new() };
+
+        TextureUpdater::register(7, |_, update| {
+            assert_eq!(update.data.len(), 32);
+            update.data.fill(update.width as u8);
+        });
+
+        let mut update = params(7);
+        assert!(handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut update)
+        ));
+
+        let data = unsafe { std::slice::from_raw_parts(update.texData.cast::<u8>(), 32) };
+        assert!(data.iter().all(|&byte| byte == 4));
+        assert_eq!(TextureUpdater::in_flight(), 1);
+
+        let first_buffer = update.texData;
+        assert!(handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureEndV2(&mut update)
+        ));
+        assert!(update.texData.is_null());
+        assert_eq!(TextureUpdater::pooled(), 1);
+
+        let mut next = params(7);
+        handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut next),
+        );
+        assert_eq!(next.texData, first_buffer);
+        assert_eq!(TextureUpdater::pooled(), 0);
+
+        let mut unknown = params(8);
+        assert!(!handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut unknown)
+        ));
+        assert!(unknown.texData.is_null());
+    }
+
+    #[test]
+    fn rejects_empty_and_unclaimed_updates() {
+        let mock = MockUnity::new();
+        crate::__private::plugin_loaded(&mock.interfaces());
+        let render_thread = unsafe { RenderThread::new() };
+
+        TextureUpdater::register(7, |_, _| panic!("Must not produce"));
+
+        let mut empty = TextureUpdateParamsV2 {
+            width: 0,
+            ..params(7)
+        };
+        assert!(!handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut empty)
+        ));
+        assert_eq!(TextureUpdater::in_flight(), 0);
+
+        // Too large, but for another plugin, so not reported
+        let mut unclaimed = TextureUpdateParamsV2 {
+            width: u32::MAX,
+            height: u32::MAX,
+            bpp: u32::MAX,
+            ..params(8)
+        };
+        assert!(!handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut unclaimed)
+        ));
+        assert!(mock.logs().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    fn reports_oversized_updates_of_known_formats() {
+        let mock = MockUnity::new();
+        crate::__private::plugin_loaded(&mock.interfaces());
+        let render_thread = unsafe { RenderThread::new() };
+
+        TextureUpdater::register(7, |_, _| panic!("Must not produce"));
+
+        // Does not fit in a usize, even on 64-bit targets
+        let mut oversized = TextureUpdateParamsV2 {
+            format: TextureFormat::R32G32B32A32_SFloat.into(),
+            width: u32::MAX,
+            height: u32::MAX,
+            ..params(7)
+        };
+        assert!(!handle_texture_update(
+            &render_thread,
+            &mut RenderingExtEvent::UpdateTextureBeginV2(&mut oversized)
+        ));
+        assert!(oversized.texData.is_null());
+
+        let logs = mock.logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].message.contains("too large"));
+    }
+}
@@ -0,0 +1,59 @@
+//! Keeps wrappers from calling into Unity after `UnityPluginUnload`.
+//!
+//! Every wrapper remembers the generation it was created in. Calls into Unity
+//! are made through [with], which only runs them while that generation is
+//! still current, and holds a read lock for the duration of the call. The
+//! generation is bumped under the write lock at the very end of unload, so it
+//! waits for calls that are still in flight on other threads.
+
+use std::cell::Cell;
+use std::sync::RwLock;
+
+plugin_static! {
+    fn generation_lock() -> &RwLock<u64> = RwLock::new(0);
+}
+
+thread_local! {
+    /// The generation this thread holds a read lock for, if any. Avoids
+    /// taking the read lock recursively, which could deadlock with a waiting writer.
+    static HELD: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Restores the previously held generation, also when the call panics
+struct HeldGuard(Option<u64>);
+
+impl Drop for HeldGuard {
+    fn drop(&mut self) {
+        HELD.set(self.0);
+    }
+}
+
+/// The current generation, which wrappers created now will be bound to
+pub(crate) fn current() -> u64 {
+    match HELD.get() {
+        Some(generation) => generation,
+        None => *generation_lock().read().unwrap_or_else(|e| e.into_inner()),
+    }
+}
+
+/// Runs the given function if the generation is still current, returning [None] otherwise
+pub(crate) fn with<R>(generation: u64, f: impl FnOnce() -> R) -> Option<R> {
+    if let Some(held) = HELD.get() {
+        return (held == generation).then(f);
+    }
+
+    let lock = generation_lock().read().unwrap_or_else(|e| e.into_inner());
+
+    if *lock != generation {
+        return None;
+    }
+
+    let _restore = HeldGuard(HELD.replace(Some(generation)));
+
+    Some(f())
+}
+
+/// Invalidates all existing wrappers, after waiting for calls in progress
+pub(crate) fn invalidate() {
+    *generation_lock().write().unwrap_or_else(|e| e.into_inner()) += 1;
+}
@@ -1,21 +1,41 @@
+#[macro_use]
+mod macros;
+
 #[cfg(feature = "log")]
 pub mod logger;
 
 #[cfg(feature = "profiler")]
 pub mod profiler;
 
+mod context;
+mod deregister;
+pub mod graphics;
+mod lease;
+pub mod probe;
 pub mod types;
 
 pub use ffi::IUnityInterfaces as RawUnityInterfaces;
 use thiserror::Error;
-use unity_native_sys as ffi;
+pub use unity_native_sys as ffi;
 
 pub use unity_native_proc_macro::*;
 
-#[macro_use]
-mod macros;
+// Allows the code generated by the proc macros to be used within this crate
+extern crate self as unity_native;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+mod panic;
+mod plugin;
+mod registered;
 
-use std::ptr::NonNull;
+pub use context::{ContextError, ContextGetError, PluginContext, context};
+pub use graphics::RenderEvents;
+pub use plugin::UnityPlugin;
+
+use std::ffi::c_void;
+use std::ptr::{NonNull, null_mut};
 
 /// The main interface provider for the Unity Native Plugin API.
 /// Constructed with a [RawUnityInterfaces] pointer, which is obtained
@@ -24,9 +44,10 @@ use std::ptr::NonNull;
 /// A convenient way to construct a function that receives this struct
 /// automatically is by using the [unity_plugin_load] and [unity_plugin_unload]
 /// to construct Unity plugin load and unload hooks.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UnityInterfaces {
     ptr: NonNull<ffi::IUnityInterfaces>,
+    generation: u64,
 }
 
 //TODO: Is this actually true? Docs are unclear, gotta test manually probably
@@ -58,6 +79,58 @@ pub unsafe trait UnityInterface:
     const GUID: ffi::UnityInterfaceGUID;
 }
 
+/// A Unity API wrapper that can be constructed from several versions of the same
+/// Unity interface, such as `IUnityProfilerV2` and the older `IUnityProfiler`.
+///
+/// Any struct implementing this trait can be obtained through [UnityInterfaces::get_best],
+/// which binds to the newest version provided by the running Unity build.
+///
+/// # Safety
+///
+/// [VersionedInterface::from_versioned_ptr] is called with the pointer Unity returned
+/// for the GUID listed next to the version in [VersionedInterface::VERSIONS].
+/// Implementors must treat that pointer as the struct matching that version only.
+pub unsafe trait VersionedInterface: Sized {
+    /// Identifies one of the supported versions of the interface
+    type Version: Copy + std::fmt::Debug + Eq + 'static;
+
+    /// The error returned when conversion fails
+    type FFIConversionError;
+
+    /// All supported versions and their GUIDs, newest first
+    const VERSIONS: &'static [(Self::Version, ffi::UnityInterfaceGUID)];
+
+    /// Constructs the wrapper from a pointer to the given version of the interface
+    ///
+    /// # Safety
+    ///
+    /// The pointer must point to the Unity interface struct of the given version
+    unsafe fn from_versioned_ptr(
+        version: Self::Version,
+        ptr: NonNull<c_void>,
+    ) -> Result<Self, Self::FFIConversionError>;
+
+    /// The version of the interface this wrapper was bound to
+    fn version(&self) -> Self::Version;
+}
+
+/// An interface implemented in Rust that is published to other native plugins
+/// through the Unity interface registry with [UnityInterfaces::register_interface].
+/// Other plugins, whether written in Rust or C++, can then fetch it using the
+/// same GUID. Interfaces published by other plugins can be looked up with
+/// [UnityInterfaces::get_plugin_interface].
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]`, and their layout must exactly match the
+/// struct that other plugins expect to find under [PluginInterface::GUID].
+/// Any function pointers in it must use the `UNITY_INTERFACE_API` calling convention
+/// expected by those plugins.
+pub unsafe trait PluginInterface: Send + Sync + 'static {
+    /// The GUID the interface is registered under
+    const GUID: ffi::UnityInterfaceGUID;
+}
+
 /// An error during the construction of the requested Unity API
 #[derive(Error, Debug)]
 pub enum GetError<T> {
@@ -71,6 +144,38 @@ pub enum GetError<T> {
     ConversionError(#[source] T),
 }
 
+/// The error returned when a Unity API is missing one or more
+/// function pointers that the wrapper requires
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{interface} is missing the functions: {}", missing.join(", "))]
+pub struct MissingFunctionsError {
+    /// The name of the wrapper that failed to construct
+    pub interface: &'static str,
+
+    /// The names of the function pointers that were NULL
+    pub missing: Vec<&'static str>,
+}
+
+/// An error while publishing a [PluginInterface]
+#[derive(Error, Debug)]
+pub enum RegisterError {
+    /// The interface registry does not provide a registration function
+    #[error("Unity does not support registering interfaces")]
+    Unsupported,
+
+    /// This plugin already registered an interface under the same GUID
+    #[error("An interface with this GUID was already registered")]
+    AlreadyRegistered,
+
+    /// The interface is a zero-sized type, which has no address to publish
+    #[error("Zero-sized interfaces cannot be registered")]
+    ZeroSized,
+
+    /// The plugin was already unloaded
+    #[error("The plugin was unloaded")]
+    Unloaded,
+}
+
 /// An error during the construction of the main UnityInterfaces wrapper
 #[derive(Error, Debug)]
 pub enum UnityInterfaceCreateErr {
@@ -83,27 +188,303 @@ impl UnityInterfaces {
     /// Constructs a safe UnityInterfaces wrapper over a raw IUnityInterfaces pointer.
     /// The given pointer must be non-NULL.
     ///
+    /// Like every wrapper in this crate, the returned struct stops calling into Unity
+    /// once the plugin is unloaded. Interfaces are then no longer found.
+    ///
     /// # Safety
     /// The caller must ensure that the provided pointer actually points to
     /// the correct struct, if it is not null. NULL pointers will not result in UB,
     /// but will simply return an [Err] instead.
     pub unsafe fn new(ptr: *mut ffi::IUnityInterfaces) -> Result<Self, UnityInterfaceCreateErr> {
         NonNull::new(ptr)
-            .map(|nonnull| UnityInterfaces { ptr: nonnull })
+            .map(UnityInterfaces::from_nonnull)
             .ok_or(UnityInterfaceCreateErr::NullPtr)
     }
 
+    pub(crate) fn from_nonnull(ptr: NonNull<ffi::IUnityInterfaces>) -> Self {
+        UnityInterfaces {
+            ptr,
+            generation: lease::current(),
+        }
+    }
+
     /// Attempts to construct a safe wrapper for the requested Unity API.
     pub fn get<T: UnityInterface>(&self) -> Result<T, GetError<T::FFIConversionError>> {
-        let iface = unsafe { self.ptr.as_ref().GetInterface.map(|f| f(T::GUID)) };
+        let iface = self.get_raw(&T::GUID).ok_or(GetError::NullPtr)?;
+
+        T::try_from(iface.cast()).map_err(GetError::ConversionError)
+    }
+
+    /// Attempts to construct a safe wrapper for the newest version of the requested
+    /// Unity API that is provided by Unity, falling back to older versions when a
+    /// newer one is missing or cannot be used. If no version could be used, the error
+    /// for the newest version that Unity did provide is returned.
+    pub fn get_best<T: VersionedInterface>(&self) -> Result<T, GetError<T::FFIConversionError>> {
+        let mut first_err = None;
+
+        for (version, guid) in T::VERSIONS {
+            let Some(ptr) = self.get_raw(guid) else {
+                continue;
+            };
+
+            match unsafe { T::from_versioned_ptr(*version, ptr.cast()) } {
+                Ok(iface) => return Ok(iface),
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        Err(first_err.map_or(GetError::NullPtr, GetError::ConversionError))
+    }
+
+    /// Looks up an interface that another plugin published under [PluginInterface::GUID].
+    /// Returns [None] if no plugin registered an interface with that GUID.
+    ///
+    /// The interface is owned by the publishing plugin, which can revoke it or be unloaded
+    /// at any time, so it is returned as a pointer. Dereferencing it is only sound while
+    /// that plugin keeps the interface published.
+    pub fn get_plugin_interface<T: PluginInterface>(&self) -> Option<NonNull<T>> {
+        self.get_raw(&T::GUID).map(NonNull::cast)
+    }
+
+    /// Publishes the given interface to other native plugins under [PluginInterface::GUID].
+    /// The interface is kept alive by this crate until the plugin is unloaded.
+    /// Zero-sized interfaces are rejected, because they have no address to publish.
+    pub fn register_interface<T: PluginInterface>(
+        &self,
+        interface: T,
+    ) -> Result<(), RegisterError> {
+        registered::register(self, interface)
+    }
+
+    /// Looks up the raw pointer registered under the given GUID.
+    ///
+    /// The split variant is preferred, because it passes the GUID as two plain integers.
+    /// The C++ declaration of the GUID struct has user-defined copy constructors,
+    /// which means compilers may pass it by reference instead of by value.
+    pub(crate) fn get_raw(
+        &self,
+        guid: &ffi::UnityInterfaceGUID,
+    ) -> Option<NonNull<ffi::IUnityInterface>> {
+        let raw = unsafe { self.ptr.as_ref() };
+
+        let ptr = lease::with(self.generation, || {
+            match (raw.GetInterfaceSplit, raw.GetInterface) {
+                (Some(get_split), _) => unsafe { get_split(guid.m_GUIDHigh, guid.m_GUIDLow) },
+                (None, Some(get)) => unsafe {
+                    get(ffi::UnityInterfaceGUID {
+                        m_GUIDHigh: guid.m_GUIDHigh,
+                        m_GUIDLow: guid.m_GUIDLow,
+                    })
+                },
+                (None, None) => null_mut(),
+            }
+        })?;
+
+        NonNull::new(ptr)
+    }
+
+    /// Registers a raw pointer under the given GUID, preferring the split variant
+    /// for the same reason as [Self::get_raw]
+    pub(crate) fn register_raw(
+        &self,
+        guid: &ffi::UnityInterfaceGUID,
+        ptr: *mut ffi::IUnityInterface,
+    ) -> Result<(), RegisterError> {
+        let raw = unsafe { self.ptr.as_ref() };
+
+        lease::with(self.generation, || {
+            match (raw.RegisterInterfaceSplit, raw.RegisterInterface) {
+                (Some(register_split), _) => unsafe {
+                    register_split(guid.m_GUIDHigh, guid.m_GUIDLow, ptr)
+                },
+                (None, Some(register)) => unsafe {
+                    register(
+                        ffi::UnityInterfaceGUID {
+                            m_GUIDHigh: guid.m_GUIDHigh,
+                            m_GUIDLow: guid.m_GUIDLow,
+                        },
+                        ptr,
+                    )
+                },
+                (None, None) => return Err(RegisterError::Unsupported),
+            }
+
+            Ok(())
+        })
+        .unwrap_or(Err(RegisterError::Unloaded))
+    }
+}
+
+/// Support functions for the code generated by the macros of this crate.
+/// Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::ffi::{c_char, c_void};
+
+    use std::os::raw::c_int;
+
+    use crate::graphics::{RenderEventErr, RenderEvents, RenderingExtQuery};
+    use crate::{RawUnityInterfaces, UnityInterfaces, UnityPlugin, ffi};
+
+    /// Reserves the IDs of a [RenderEvents] type listed in `#[unity_plugin(render_events(..))]`
+    pub type ReserveRenderEvents = fn() -> Result<c_int, RenderEventErr>;
+
+    pub use crate::panic::guard;
+
+    /// The body of the `UnityPluginLoad` generated by `#[unity_plugin]`
+    pub fn load_plugin<T: UnityPlugin>(
+        interfaces: *mut RawUnityInterfaces,
+        render_events: &[ReserveRenderEvents],
+    ) {
+        crate::plugin::load::<T>(interfaces, render_events);
+    }
+
+    /// The body of the `UnityPluginUnload` generated by `#[unity_plugin]`
+    pub fn unload_plugin() {
+        crate::plugin::unload();
+    }
+
+    /// The body of the `GetRenderEventFunc` generated by `#[unity_plugin]`
+    pub fn render_event_func() -> ffi::UnityRenderingEvent {
+        crate::plugin::render_event_func()
+    }
+
+    /// The body of the `GetRenderCommandEventID` generated by `#[unity_plugin]`
+    pub fn render_command_event_id() -> c_int {
+        guard(
+            "GetRenderCommandEventID",
+            -1,
+            || match crate::graphics::RenderCommandQueue::event_id() {
+                Ok(id) => id,
+                Err(err) => {
+                    crate::panic::log_error(&format!(
+                        "Could not reserve the render command event ID: {err}"
+                    ));
+                    -1
+                }
+            },
+        )
+    }
+
+    /// The body of the `GetRenderEventAndDataFunc` generated by `#[unity_plugin]`
+    pub fn render_event_and_data_func() -> ffi::UnityRenderingEventAndData {
+        crate::graphics::render_event_and_data_func()
+    }
+
+    /// The body of the `UnityRenderingExtEvent` generated by `#[unity_plugin]`
+    pub fn rendering_ext_event(event: ffi::UnityRenderingExtEventType, data: *mut c_void) {
+        crate::plugin::rendering_ext_event(event, data);
+    }
+
+    /// The body of the `UnityRenderingExtQuery` generated by `#[unity_plugin(rendering_ext_queries = ..)]`
+    pub fn rendering_ext_query(
+        queries: RenderingExtQuery,
+        query: ffi::UnityRenderingExtQueryType,
+    ) -> bool {
+        guard("UnityRenderingExtQuery", false, || queries.answers(query))
+    }
+
+    /// The body of the `GetCustomBlitFunc` generated by `#[unity_plugin]`
+    pub fn custom_blit_func() -> ffi::UnityRenderingEventAndData {
+        crate::graphics::custom_blit_func()
+    }
+
+    /// The body of the `GetCustomBlitCommandID` generated by `#[unity_plugin]`
+    ///
+    /// # Safety
+    /// `name` must be NULL or a NUL terminated string
+    pub unsafe fn custom_blit_command_id(name: *const c_char) -> c_int {
+        guard("GetCustomBlitCommandID", -1, || unsafe {
+            crate::graphics::custom_blit_command_id(name)
+        })
+    }
+
+    /// The body of the `Get<Enum>RenderEventFunc` generated by `#[derive(RenderEvents)]`
+    pub fn render_events_func<E: RenderEvents>() -> ffi::UnityRenderingEvent {
+        crate::graphics::render_event_func::<E>()
+    }
+
+    /// The body of the `Get<Enum>BaseEventID` generated by `#[derive(RenderEvents)]`
+    pub fn render_events_base_id<E: RenderEvents>() -> c_int {
+        crate::graphics::base_event_id::<E>()
+    }
+
+    /// Called by the generated `UnityPluginLoad`, before the user-provided load function
+    pub fn plugin_loaded(interfaces: &UnityInterfaces) {
+        crate::panic::set_logger(interfaces);
+        crate::context::init(interfaces);
+    }
+
+    /// Called by the generated `UnityPluginUnload`, before the user-provided unload function
+    pub fn plugin_unloading() {
+        crate::deregister::undo_all();
+    }
+
+    /// Called by the generated `UnityPluginUnload`, after the user-provided unload function
+    pub fn plugin_unloaded() {
+        crate::registered::release_all();
+        crate::graphics::clear_render_data();
+        crate::graphics::clear_render_commands();
+        crate::graphics::clear_gpu_resources();
+        crate::graphics::clear_texture_updates();
+        crate::graphics::clear_custom_blits();
+        crate::context::teardown();
+        crate::panic::clear_logger();
+
+        // Last, so that everything above can still call into Unity
+        crate::lease::invalidate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[derive(UnityInterface)]
+    #[unity_interface(
+        guid(0x7CAC7D1D8A0C4EAB, 0x9C4C1E6A7F23B1A1),
+        ffi = ffi::IUnityLog,
+        required(Log)
+    )]
+    struct CustomLog {
+        _ptr: NonNull<ffi::IUnityLog>,
+    }
+
+    #[test]
+    fn derived_interface_checks_functions() {
+        let mock = MockUnity::new();
+        let interfaces = mock.interfaces();
+
+        assert!(matches!(
+            interfaces.get::<CustomLog>(),
+            Err(GetError::NullPtr)
+        ));
+
+        let table = Box::into_raw(Box::<ffi::IUnityLog>::default());
+        interfaces
+            .register_raw(&CustomLog::GUID, table.cast())
+            .unwrap();
 
-        let as_nonnull = iface.and_then(|ptr| NonNull::new(ptr as *mut T::FFIType));
-        if as_nonnull.is_none() {
-            return Err(GetError::NullPtr);
+        match interfaces.get::<CustomLog>() {
+            Err(GetError::ConversionError(err)) => {
+                assert_eq!(err.interface, "CustomLog");
+                assert_eq!(err.missing, ["Log"]);
+            }
+            other => panic!("expected missing functions, got {:?}", other.err()),
         }
 
-        let iface = as_nonnull.unwrap();
+        let mock_log = interfaces
+            .get_raw(&unity_api_guid!(0x9E7507FA5B444D5D 0x92FB979515EA83FC))
+            .unwrap();
+        unsafe { (*table).Log = mock_log.cast::<ffi::IUnityLog>().as_ref().Log };
+        assert!(interfaces.get::<CustomLog>().is_ok());
 
-        T::try_from(iface).map_err(GetError::ConversionError)
+        interfaces
+            .register_raw(&CustomLog::GUID, null_mut())
+            .unwrap();
+        drop(unsafe { Box::from_raw(table) });
     }
 }
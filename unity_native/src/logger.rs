@@ -1,8 +1,11 @@
+use std::os::raw::{c_char, c_int};
 use std::{ffi::CString, ptr::NonNull};
 
 use log::Log;
 
-use crate::{UnityInterface, ffi, unity_api_guid};
+use crate::{MissingFunctionsError, UnityInterface, ffi, lease};
+
+type LogFn = unity_api_fn_type!(fn(ffi::UnityLogType, *const c_char, *const c_char, c_int));
 
 /// A wrapper for the Unity Logging API. It supports
 /// both manual logging using [UnityLogger::log_generic] (and friends),
@@ -10,7 +13,8 @@ use crate::{UnityInterface, ffi, unity_api_guid};
 /// for [UnityLogger] that implements the [log::Log] trait. This allows
 /// it to be used with the standard [log::log!] macros.
 pub struct UnityLogger {
-    ptr: NonNull<ffi::IUnityLog>,
+    log: LogFn,
+    generation: u64,
 }
 
 unsafe impl Send for UnityLogger {}
@@ -18,15 +22,20 @@ unsafe impl Sync for UnityLogger {}
 
 unsafe impl UnityInterface for UnityLogger {
     type FFIType = ffi::IUnityLog;
-    type FFIConversionError = ();
+    type FFIConversionError = MissingFunctionsError;
     const GUID: ffi::UnityInterfaceGUID = unity_api_guid!(0x9E7507FA5B444D5D 0x92FB979515EA83FC);
 }
 
 impl TryFrom<NonNull<ffi::IUnityLog>> for UnityLogger {
-    type Error = ();
+    type Error = MissingFunctionsError;
 
     fn try_from(value: NonNull<ffi::IUnityLog>) -> Result<Self, Self::Error> {
-        Ok(Self { ptr: value })
+        let (log,) = require_fns!("UnityLogger", unsafe { value.as_ref() }, Log)?;
+
+        Ok(Self {
+            log,
+            generation: lease::current(),
+        })
     }
 }
 
@@ -95,24 +104,23 @@ impl UnityLogger {
     /// Logs a generic message using the Unity Log API, with the provided
     /// level. The filename and line are supposed to be the file and line of
     /// the function generating the log, so using the Rust [file!] and [line!]
-    /// macros is recommended
+    /// macros is recommended.
+    ///
+    /// Messages logged after the plugin was unloaded are silently dropped.
     pub fn log_generic(&self, level: UnityLogType, msg: &str, filename: &str, line: u32) {
-        let line_c = std::os::raw::c_int::try_from(line).unwrap_or(std::os::raw::c_int::MIN);
+        let line_c = c_int::try_from(line).unwrap_or(c_int::MIN);
 
         let message_c_str = filter_str_to_c_string(msg);
         let filename_c_str = filter_str_to_c_string(filename);
 
-        unsafe {
-            let logger_raw = self.ptr.as_ref();
-            let logfn = logger_raw.Log.unwrap();
-
-            logfn(
+        lease::with(self.generation, || unsafe {
+            (self.log)(
                 level.into(),
                 message_c_str.as_ptr(),
                 filename_c_str.as_ptr(),
                 line_c,
             );
-        }
+        });
     }
 
     /// Convenience wrapper for [`UnityLogger::log_generic`] with
@@ -7,3 +7,89 @@ macro_rules! unity_api_guid {
         }
     };
 }
+
+/// Defines a function with the calling convention that bindgen generates for
+/// `UNITY_INTERFACE_API` on the current target, so that it coerces into the
+/// function pointer fields of the raw FFI structs. That is `stdcall` on 32-bit
+/// Windows and the C calling convention everywhere else.
+macro_rules! unity_api_fn {
+    (
+        $(#[$attr:meta])*
+        $vis:vis unsafe fn $name:ident $(<$gen:ident: $bound:path>)? ($($arg:ident: $argty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        #[cfg(all(windows, target_arch = "x86"))]
+        $(#[$attr])*
+        $vis unsafe extern "stdcall" fn $name $(<$gen: $bound>)? ($($arg: $argty),*) $(-> $ret)? $body
+
+        #[cfg(not(all(windows, target_arch = "x86")))]
+        $(#[$attr])*
+        $vis unsafe extern "C" fn $name $(<$gen: $bound>)? ($($arg: $argty),*) $(-> $ret)? $body
+    };
+}
+
+/// Names the non-optional function pointer type that bindgen generates for
+/// `UNITY_INTERFACE_API` function pointer fields on the current target.
+/// Used to store function pointers that were validated during wrapper construction.
+///
+/// Exported so that tests can check generated entry points against it, not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(all(windows, target_arch = "x86"))]
+macro_rules! unity_api_fn_type {
+    (fn($($argty:ty),* $(,)?) $(-> $ret:ty)?) => {
+        unsafe extern "stdcall" fn($($argty),*) $(-> $ret)?
+    };
+}
+
+/// Names the non-optional function pointer type that bindgen generates for
+/// `UNITY_INTERFACE_API` function pointer fields on the current target.
+/// Used to store function pointers that were validated during wrapper construction.
+///
+/// Exported so that tests can check generated entry points against it, not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(all(windows, target_arch = "x86")))]
+macro_rules! unity_api_fn_type {
+    (fn($($argty:ty),* $(,)?) $(-> $ret:ty)?) => {
+        unsafe extern "C" fn($($argty),*) $(-> $ret)?
+    };
+}
+
+/// Takes the given function pointer fields out of a raw Unity API table,
+/// returning them as a tuple of non-optional function pointers. If any of
+/// them is NULL, a [crate::MissingFunctionsError] naming all missing fields is returned instead.
+///
+/// Exported for the code generated by `#[derive(UnityInterface)]`, not part of the public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_fns {
+    ($interface:literal, $table:expr, $($field:ident),+ $(,)?) => {{
+        let table = $table;
+
+        let missing: Vec<&'static str> = [$((stringify!($field), table.$field.is_none())),+]
+            .into_iter()
+            .filter_map(|(name, missing)| missing.then_some(name))
+            .collect();
+
+        if missing.is_empty() {
+            Ok(($(table.$field.unwrap(),)+))
+        } else {
+            Err($crate::MissingFunctionsError {
+                interface: $interface,
+                missing,
+            })
+        }
+    }};
+}
+
+/// Declares global plugin state behind an accessor function returning a `&'static`.
+/// All of it is reset by the unload path, which tests run by dropping their
+/// [crate::mock::MockUnity].
+macro_rules! plugin_static {
+    ($vis:vis fn $accessor:ident() -> &$ty:ty = $init:expr;) => {
+        $vis fn $accessor() -> &'static $ty {
+            static STATE: $ty = $init;
+            &STATE
+        }
+    };
+}
@@ -0,0 +1,992 @@
+//! An in-process stand-in for the Unity plugin host, so plugin logic can be
+//! unit tested without Unity loading the library.
+//!
+//! A [MockUnity] builds a fake [RawUnityInterfaces] table whose `GetInterface`
+//! serves recording implementations of `IUnityLog`, `IUnityProfilerV2`,
+//! `IUnityProfilerCallbacksV2`, `IUnityGraphics` and `IUnityMemoryManager`,
+//! as well as the older `IUnityProfiler` and `IUnityProfilerCallbacks`. The regular wrappers such as
+//! [UnityLogger](crate::logger::UnityLogger) and
+//! [UnityProfiler](crate::profiler::UnityProfiler) run unchanged against it,
+//! and everything they do can be inspected afterwards.
+//!
+//! The Unity function tables carry no user data, so the recorded state is
+//! process-wide. Only one [MockUnity] can exist at a time; creating a second
+//! one blocks until the first is dropped, which serialises tests that use it.
+//! Dropping it runs the unload path of this crate, like `UnityPluginUnload`
+//! would, so the next test starts without any plugin state.
+//!
+//! Available with the `mock` feature.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char, c_int, c_ulonglong, c_void};
+use std::ptr::null_mut;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{RawUnityInterfaces, UnityInterfaces, ffi};
+
+const LOG_GUID: (u64, u64) = (0x9E7507FA5B444D5D, 0x92FB979515EA83FC);
+const PROFILER_V2_GUID: (u64, u64) = (0xB957E0189CB6A30B, 0x83CE589AE85B9068);
+const PROFILER_V1_GUID: (u64, u64) = (0x2CE79ED8316A4833, 0x87076B2013E1571F);
+const PROFILER_CALLBACKS_V2_GUID: (u64, u64) = (0x5DEB59E88F2D4571, 0x81E8583069A5E33C);
+const PROFILER_CALLBACKS_V1_GUID: (u64, u64) = (0x572FDB38CE3C4B1F, 0xA6071A9A7C4F52D8);
+const GRAPHICS_GUID: (u64, u64) = (0x7CBA0A9CA4DDB544, 0x8C5AD4926EB17B11);
+const MEMORY_MANAGER_GUID: (u64, u64) = (0xBAF9E57C61A811EC, 0xC5A7CC7861A811EC);
+
+static MOCK_LOCK: Mutex<()> = Mutex::new(());
+static STATE: Mutex<Option<MockState>> = Mutex::new(None);
+
+/// A single message received through the mocked `IUnityLog`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: ffi::UnityLogType,
+    pub message: String,
+    pub file: String,
+    pub line: i32,
+}
+
+/// A profiler marker created through the mocked `IUnityProfilerV2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerRecord {
+    pub name: String,
+    pub category: ffi::UnityProfilerCategoryId,
+    pub flags: ffi::UnityProfilerMarkerFlags,
+    pub metadata: Vec<MarkerMetadataRecord>,
+}
+
+/// A metadata description set on a marker with `SetMarkerMetadataName`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerMetadataRecord {
+    pub index: i32,
+    pub name: String,
+    pub datatype: ffi::UnityProfilerMarkerDataType,
+    pub unit: ffi::UnityProfilerMarkerDataUnit,
+}
+
+/// The kind of a recorded profiler event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Begin,
+    End,
+    Single,
+    Other(ffi::UnityProfilerMarkerEventType),
+}
+
+/// A profiler event emitted through the mocked `IUnityProfilerV2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRecord {
+    pub marker: String,
+    pub kind: EventKind,
+    pub data: Vec<EventDataRecord>,
+}
+
+/// A single metadata item attached to a recorded event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDataRecord {
+    pub datatype: ffi::UnityProfilerMarkerDataType,
+    pub bytes: Vec<u8>,
+}
+
+/// A thread registered through the mocked `IUnityProfilerV2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadRecord {
+    pub id: ffi::UnityProfilerThreadId,
+    pub group: String,
+    pub name: String,
+    pub registered: bool,
+}
+
+/// An allocator created through the mocked `IUnityMemoryManager`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocatorRecord {
+    pub area: String,
+    pub object: String,
+    pub destroyed: bool,
+    pub live_allocations: usize,
+}
+
+struct MockMarker {
+    desc: ffi::UnityProfilerMarkerDesc,
+    _name: CString,
+    record: MarkerRecord,
+}
+
+struct MockAllocator {
+    record: AllocatorRecord,
+    live: HashMap<usize, std::alloc::Layout>,
+}
+
+struct MockState {
+    log: Box<ffi::IUnityLog>,
+    profiler: Box<ffi::IUnityProfilerV2>,
+    profiler_v1: Box<ffi::IUnityProfiler>,
+    profiler_callbacks: Box<ffi::IUnityProfilerCallbacksV2>,
+    profiler_callbacks_v1: Box<ffi::IUnityProfilerCallbacks>,
+    graphics: Box<ffi::IUnityGraphics>,
+    memory: Box<ffi::IUnityMemoryManager>,
+
+    hidden: Vec<(u64, u64)>,
+    registered: HashMap<(u64, u64), usize>,
+
+    profiler_available: bool,
+    profiler_enabled: bool,
+    renderer: ffi::UnityGfxRenderer,
+    next_event_id: c_int,
+
+    logs: Vec<LogRecord>,
+    // Boxed so that the descriptors handed out to the plugin keep their address
+    #[allow(clippy::vec_box)]
+    markers: Vec<Box<MockMarker>>,
+    events: Vec<EventRecord>,
+    threads: Vec<ThreadRecord>,
+    categories: Vec<String>,
+    device_callbacks: Vec<ffi::IUnityGraphicsDeviceEventCallback>,
+    frame_callbacks: Vec<(ffi::IUnityProfilerFrameCallback, usize)>,
+    flow_callbacks: Vec<(ffi::IUnityProfilerFlowEventCallback, usize)>,
+    allocators: Vec<MockAllocator>,
+}
+
+// The raw pointers in the state only ever point into memory owned by the state itself
+unsafe impl Send for MockState {}
+
+/// A fake Unity host. See the [module documentation](self) for details.
+pub struct MockUnity {
+    raw: Box<RawUnityInterfaces>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl std::fmt::Debug for MockUnity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockUnity").finish_non_exhaustive()
+    }
+}
+
+impl Default for MockUnity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockUnity {
+    /// Creates a new mocked Unity host, with an available and enabled profiler
+    /// and the Null renderer. Blocks while another [MockUnity] is alive.
+    pub fn new() -> Self {
+        let lock = MOCK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut log = Box::<ffi::IUnityLog>::default();
+        log.Log = Some(mock_log);
+
+        let mut profiler = Box::<ffi::IUnityProfilerV2>::default();
+        profiler.EmitEvent = Some(mock_emit_event);
+        profiler.IsEnabled = Some(mock_is_enabled);
+        profiler.IsAvailable = Some(mock_is_available);
+        profiler.CreateMarker = Some(mock_create_marker);
+        profiler.SetMarkerMetadataName = Some(mock_set_marker_metadata_name);
+        profiler.CreateCategory = Some(mock_create_category);
+        profiler.RegisterThread = Some(mock_register_thread);
+        profiler.UnregisterThread = Some(mock_unregister_thread);
+
+        let mut profiler_v1 = Box::<ffi::IUnityProfiler>::default();
+        profiler_v1.EmitEvent = Some(mock_emit_event);
+        profiler_v1.IsEnabled = Some(mock_is_enabled);
+        profiler_v1.IsAvailable = Some(mock_is_available);
+        profiler_v1.CreateMarker = Some(mock_create_marker);
+        profiler_v1.SetMarkerMetadataName = Some(mock_set_marker_metadata_name);
+        profiler_v1.RegisterThread = Some(mock_register_thread);
+        profiler_v1.UnregisterThread = Some(mock_unregister_thread);
+
+        let mut profiler_callbacks = Box::<ffi::IUnityProfilerCallbacksV2>::default();
+        profiler_callbacks.RegisterFrameCallback = Some(mock_register_frame_callback);
+        profiler_callbacks.UnregisterFrameCallback = Some(mock_unregister_frame_callback);
+        profiler_callbacks.RegisterFlowEventCallback = Some(mock_register_flow_event_callback);
+        profiler_callbacks.UnregisterFlowEventCallback = Some(mock_unregister_flow_event_callback);
+
+        let mut profiler_callbacks_v1 = Box::<ffi::IUnityProfilerCallbacks>::default();
+        profiler_callbacks_v1.RegisterFrameCallback = Some(mock_register_frame_callback);
+        profiler_callbacks_v1.UnregisterFrameCallback = Some(mock_unregister_frame_callback);
+
+        let mut graphics = Box::<ffi::IUnityGraphics>::default();
+        graphics.GetRenderer = Some(mock_get_renderer);
+        graphics.RegisterDeviceEventCallback = Some(mock_register_device_event_callback);
+        graphics.UnregisterDeviceEventCallback = Some(mock_unregister_device_event_callback);
+        graphics.ReserveEventIDRange = Some(mock_reserve_event_id_range);
+
+        let mut memory = Box::<ffi::IUnityMemoryManager>::default();
+        memory.CreateAllocator = Some(mock_create_allocator);
+        memory.DestroyAllocator = Some(mock_destroy_allocator);
+        memory.Allocate = Some(mock_allocate);
+        memory.Deallocate = Some(mock_deallocate);
+        memory.Reallocate = Some(mock_reallocate);
+
+        let mut raw = Box::<RawUnityInterfaces>::default();
+        raw.GetInterface = Some(mock_get_interface);
+        raw.RegisterInterface = Some(mock_register_interface);
+        raw.GetInterfaceSplit = Some(mock_get_interface_split);
+        raw.RegisterInterfaceSplit = Some(mock_register_interface_split);
+
+        *state_lock() = Some(MockState {
+            log,
+            profiler,
+            profiler_v1,
+            profiler_callbacks,
+            profiler_callbacks_v1,
+            graphics,
+            memory,
+            hidden: Vec::new(),
+            registered: HashMap::new(),
+            profiler_available: true,
+            profiler_enabled: true,
+            renderer: ffi::UnityGfxRenderer::kUnityGfxRendererNull,
+            next_event_id: 0,
+            logs: Vec::new(),
+            markers: Vec::new(),
+            events: Vec::new(),
+            threads: Vec::new(),
+            categories: Vec::new(),
+            device_callbacks: Vec::new(),
+            frame_callbacks: Vec::new(),
+            flow_callbacks: Vec::new(),
+            allocators: Vec::new(),
+        });
+
+        Self { raw, _lock: lock }
+    }
+
+    /// The raw interface table, as Unity would pass it to `UnityPluginLoad`
+    pub fn raw(&self) -> *mut RawUnityInterfaces {
+        (&raw const *self.raw).cast_mut()
+    }
+
+    /// A safe [UnityInterfaces] wrapper around the mocked interface table
+    pub fn interfaces(&self) -> UnityInterfaces {
+        unsafe { UnityInterfaces::new(self.raw()) }.expect("Mock interface table is never NULL")
+    }
+
+    /// Makes `GetInterface` return NULL for the given GUID, as an older Unity
+    /// build without that interface would
+    pub fn hide_interface(&self, guid: ffi::UnityInterfaceGUID) {
+        with_state(|s| s.hidden.push((guid.m_GUIDHigh, guid.m_GUIDLow)));
+    }
+
+    /// Sets the value returned by the profiler `IsAvailable` call. Only affects
+    /// profiler wrappers created afterwards.
+    pub fn set_profiler_available(&self, available: bool) {
+        with_state(|s| s.profiler_available = available);
+    }
+
+    /// Sets the value returned by the profiler `IsEnabled` call
+    pub fn set_profiler_enabled(&self, enabled: bool) {
+        with_state(|s| s.profiler_enabled = enabled);
+    }
+
+    /// Sets the renderer reported by `IUnityGraphics::GetRenderer`
+    pub fn set_renderer(&self, renderer: ffi::UnityGfxRenderer) {
+        with_state(|s| s.renderer = renderer);
+    }
+
+    /// Invokes every registered graphics device event callback, in registration order
+    pub fn fire_device_event(&self, event: ffi::UnityGfxDeviceEventType) {
+        // Copied out so that callbacks can call back into the mock
+        let callbacks = with_state(|s| s.device_callbacks.clone());
+
+        for callback in callbacks.into_iter().flatten() {
+            unsafe { callback(event) };
+        }
+    }
+
+    /// The number of currently registered graphics device event callbacks
+    pub fn device_event_callback_count(&self) -> usize {
+        with_state(|s| s.device_callbacks.len())
+    }
+
+    /// Invokes every registered profiler frame callback, in registration order
+    pub fn fire_profiler_frame(&self) {
+        let callbacks = with_state(|s| s.frame_callbacks.clone());
+
+        for (callback, user_data) in callbacks {
+            if let Some(callback) = callback {
+                unsafe { callback(user_data as *mut c_void) };
+            }
+        }
+    }
+
+    /// Invokes every registered profiler flow event callback, in registration order
+    pub fn fire_flow_event(&self, event: ffi::UnityProfilerFlowEventType, flow_id: u32) {
+        let callbacks = with_state(|s| s.flow_callbacks.clone());
+
+        for (callback, user_data) in callbacks {
+            if let Some(callback) = callback {
+                unsafe { callback(event, flow_id, user_data as *mut c_void) };
+            }
+        }
+    }
+
+    /// The number of currently registered profiler frame and flow event callbacks
+    pub fn profiler_callback_count(&self) -> usize {
+        with_state(|s| s.frame_callbacks.len() + s.flow_callbacks.len())
+    }
+
+    /// The pointer registered under the given GUID through `RegisterInterface`, if any
+    pub fn registered_interface(&self, guid: ffi::UnityInterfaceGUID) -> Option<*mut c_void> {
+        with_state(|s| {
+            s.registered
+                .get(&(guid.m_GUIDHigh, guid.m_GUIDLow))
+                .map(|addr| *addr as *mut c_void)
+        })
+    }
+
+    /// All messages logged so far
+    pub fn logs(&self) -> Vec<LogRecord> {
+        with_state(|s| s.logs.clone())
+    }
+
+    /// All profiler markers created so far
+    pub fn markers(&self) -> Vec<MarkerRecord> {
+        with_state(|s| s.markers.iter().map(|m| m.record.clone()).collect())
+    }
+
+    /// All profiler events emitted so far
+    pub fn events(&self) -> Vec<EventRecord> {
+        with_state(|s| s.events.clone())
+    }
+
+    /// All threads registered with the profiler so far
+    pub fn threads(&self) -> Vec<ThreadRecord> {
+        with_state(|s| s.threads.clone())
+    }
+
+    /// The names of all profiler categories created so far
+    pub fn categories(&self) -> Vec<String> {
+        with_state(|s| s.categories.clone())
+    }
+
+    /// All allocators created through the memory manager so far
+    pub fn allocators(&self) -> Vec<AllocatorRecord> {
+        with_state(|s| {
+            s.allocators
+                .iter()
+                .map(|a| AllocatorRecord {
+                    live_allocations: a.live.len(),
+                    ..a.record.clone()
+                })
+                .collect()
+        })
+    }
+
+    /// Forgets all recorded logs and profiler events. Markers, threads and
+    /// callbacks stay registered.
+    pub fn clear_records(&self) {
+        with_state(|s| {
+            s.logs.clear();
+            s.events.clear();
+        });
+    }
+}
+
+impl Drop for MockUnity {
+    fn drop(&mut self) {
+        // Before taking the state, because unregistering callbacks still calls into it
+        crate::plugin::unload();
+
+        let state = state_lock().take();
+
+        // Free any allocations the code under test leaked
+        if let Some(state) = state {
+            for allocator in state.allocators {
+                for (addr, layout) in allocator.live {
+                    unsafe { std::alloc::dealloc(addr as *mut u8, layout) };
+                }
+            }
+        }
+    }
+}
+
+fn state_lock() -> MutexGuard<'static, Option<MockState>> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn with_state<R>(f: impl FnOnce(&mut MockState) -> R) -> R {
+    let mut lock = state_lock();
+    let state = lock
+        .as_mut()
+        .expect("Unity API called while no MockUnity is alive");
+
+    f(state)
+}
+
+unsafe fn string_from_c(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    unsafe { CStr::from_ptr(ptr) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn lookup_interface(high: c_ulonglong, low: c_ulonglong) -> *mut ffi::IUnityInterface {
+    with_state(|s| {
+        let key = (high, low);
+
+        if s.hidden.contains(&key) {
+            return null_mut();
+        }
+
+        let ptr: *mut c_void = match key {
+            LOG_GUID => (&raw mut *s.log).cast(),
+            PROFILER_V2_GUID => (&raw mut *s.profiler).cast(),
+            PROFILER_V1_GUID => (&raw mut *s.profiler_v1).cast(),
+            PROFILER_CALLBACKS_V2_GUID => (&raw mut *s.profiler_callbacks).cast(),
+            PROFILER_CALLBACKS_V1_GUID => (&raw mut *s.profiler_callbacks_v1).cast(),
+            GRAPHICS_GUID => (&raw mut *s.graphics).cast(),
+            MEMORY_MANAGER_GUID => (&raw mut *s.memory).cast(),
+            _ => s
+                .registered
+                .get(&key)
+                .map_or(null_mut(), |addr| *addr as *mut c_void),
+        };
+
+        ptr.cast()
+    })
+}
+
+fn register_interface(high: c_ulonglong, low: c_ulonglong, ptr: *mut ffi::IUnityInterface) {
+    with_state(|s| {
+        if ptr.is_null() {
+            s.registered.remove(&(high, low));
+        } else {
+            s.registered.insert((high, low), ptr as usize);
+        }
+    });
+}
+
+unity_api_fn! {
+    unsafe fn mock_get_interface(guid: ffi::UnityInterfaceGUID) -> *mut ffi::IUnityInterface {
+        lookup_interface(guid.m_GUIDHigh, guid.m_GUIDLow)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_get_interface_split(high: c_ulonglong, low: c_ulonglong) -> *mut ffi::IUnityInterface {
+        lookup_interface(high, low)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_interface(guid: ffi::UnityInterfaceGUID, ptr: *mut ffi::IUnityInterface) {
+        register_interface(guid.m_GUIDHigh, guid.m_GUIDLow, ptr)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_interface_split(high: c_ulonglong, low: c_ulonglong, ptr: *mut ffi::IUnityInterface) {
+        register_interface(high, low, ptr)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_log(level: ffi::UnityLogType, message: *const c_char, file: *const c_char, line: c_int) {
+        let record = unsafe {
+            LogRecord {
+                level,
+                message: string_from_c(message),
+                file: string_from_c(file),
+                line,
+            }
+        };
+
+        with_state(|s| s.logs.push(record));
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_emit_event(
+        desc: *const ffi::UnityProfilerMarkerDesc,
+        event_type: ffi::UnityProfilerMarkerEventType,
+        data_count: u16,
+        data: *const ffi::UnityProfilerMarkerData,
+    ) {
+        let marker = unsafe { desc.as_ref() }
+            .map(|desc| unsafe { string_from_c(desc.name) })
+            .unwrap_or_default();
+
+        let kind = match u32::from(event_type) {
+            x if x == ffi::UnityProfilerMarkerEventType_::kUnityProfilerMarkerEventTypeBegin.0 => EventKind::Begin,
+            x if x == ffi::UnityProfilerMarkerEventType_::kUnityProfilerMarkerEventTypeEnd.0 => EventKind::End,
+            x if x == ffi::UnityProfilerMarkerEventType_::kUnityProfilerMarkerEventTypeSingle.0 => EventKind::Single,
+            _ => EventKind::Other(event_type),
+        };
+
+        let items = if data.is_null() || data_count == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(data, data_count as usize) }
+        };
+
+        let data = items
+            .iter()
+            .map(|item| EventDataRecord {
+                datatype: item.type_,
+                bytes: if item.ptr.is_null() {
+                    Vec::new()
+                } else {
+                    unsafe { std::slice::from_raw_parts(item.ptr.cast::<u8>(), item.size as usize) }
+                        .to_vec()
+                },
+            })
+            .collect();
+
+        with_state(|s| s.events.push(EventRecord { marker, kind, data }));
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_is_enabled() -> c_int {
+        with_state(|s| s.profiler_enabled as c_int)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_is_available() -> c_int {
+        with_state(|s| s.profiler_available as c_int)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_create_marker(
+        desc_out: *mut *const ffi::UnityProfilerMarkerDesc,
+        name: *const c_char,
+        category: ffi::UnityProfilerCategoryId,
+        flags: ffi::UnityProfilerMarkerFlags,
+        _event_data_count: c_int,
+    ) -> c_int {
+        if desc_out.is_null() || name.is_null() {
+            return 1;
+        }
+
+        let name_c = unsafe { CStr::from_ptr(name) }.to_owned();
+
+        with_state(|s| {
+            let mut marker = Box::new(MockMarker {
+                desc: ffi::UnityProfilerMarkerDesc::default(),
+                record: MarkerRecord {
+                    name: name_c.to_string_lossy().into_owned(),
+                    category,
+                    flags,
+                    metadata: Vec::new(),
+                },
+                _name: name_c,
+            });
+
+            marker.desc.id = s.markers.len() as ffi::UnityProfilerMarkerId;
+            marker.desc.flags = flags;
+            marker.desc.categoryId = category;
+            marker.desc.name = marker._name.as_ptr();
+
+            unsafe { *desc_out = &raw const marker.desc };
+            s.markers.push(marker);
+        });
+
+        0
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_set_marker_metadata_name(
+        desc: *const ffi::UnityProfilerMarkerDesc,
+        index: c_int,
+        name: *const c_char,
+        datatype: ffi::UnityProfilerMarkerDataType,
+        unit: ffi::UnityProfilerMarkerDataUnit,
+    ) -> c_int {
+        let name = unsafe { string_from_c(name) };
+
+        with_state(|s| {
+            match s.markers.iter_mut().find(|m| std::ptr::eq(&m.desc, desc)) {
+                Some(marker) => {
+                    marker.record.metadata.push(MarkerMetadataRecord {
+                        index,
+                        name,
+                        datatype,
+                        unit,
+                    });
+                    0
+                }
+                None => 1,
+            }
+        })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_create_category(
+        category_out: *mut ffi::UnityProfilerCategoryId,
+        name: *const c_char,
+        _unused: u32,
+    ) -> c_int {
+        if category_out.is_null() {
+            return 1;
+        }
+
+        let name = unsafe { string_from_c(name) };
+
+        with_state(|s| {
+            s.categories.push(name);
+            // Stay clear of the builtin categories
+            unsafe { *category_out = (s.categories.len() + 0x100) as ffi::UnityProfilerCategoryId };
+        });
+
+        0
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_thread(
+        id_out: *mut ffi::UnityProfilerThreadId,
+        group: *const c_char,
+        name: *const c_char,
+    ) -> c_int {
+        let (group, name) = unsafe { (string_from_c(group), string_from_c(name)) };
+
+        with_state(|s| {
+            let id = s.threads.len() as ffi::UnityProfilerThreadId + 1;
+
+            s.threads.push(ThreadRecord {
+                id,
+                group,
+                name,
+                registered: true,
+            });
+
+            if !id_out.is_null() {
+                unsafe { *id_out = id };
+            }
+        });
+
+        0
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_unregister_thread(id: ffi::UnityProfilerThreadId) -> c_int {
+        with_state(|s| {
+            // ID 0 means "the current thread", which the mock doesn't track
+            let target = if id == 0 {
+                s.threads.iter_mut().rev().find(|t| t.registered)
+            } else {
+                s.threads.iter_mut().find(|t| t.id == id && t.registered)
+            };
+
+            match target {
+                Some(thread) => {
+                    thread.registered = false;
+                    0
+                }
+                None => 1,
+            }
+        })
+    }
+}
+
+/// Removes the callback with the given function and user data, returning
+/// a non-zero error code like Unity does if it was never registered
+fn remove_callback<F: Copy>(
+    callbacks: &mut Vec<(Option<F>, usize)>,
+    callback: Option<usize>,
+    user_data: usize,
+    addr: impl Fn(F) -> usize,
+) -> c_int {
+    let position = callbacks
+        .iter()
+        .position(|(f, data)| f.map(&addr) == callback && *data == user_data);
+
+    match position {
+        Some(index) => {
+            callbacks.remove(index);
+            0
+        }
+        None => 1,
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_frame_callback(callback: ffi::IUnityProfilerFrameCallback, user_data: *mut c_void) -> c_int {
+        with_state(|s| s.frame_callbacks.push((callback, user_data as usize)));
+        0
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_unregister_frame_callback(callback: ffi::IUnityProfilerFrameCallback, user_data: *mut c_void) -> c_int {
+        with_state(|s| {
+            remove_callback(&mut s.frame_callbacks, callback.map(|f| f as usize), user_data as usize, |f| f as usize)
+        })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_flow_event_callback(callback: ffi::IUnityProfilerFlowEventCallback, user_data: *mut c_void) -> c_int {
+        with_state(|s| s.flow_callbacks.push((callback, user_data as usize)));
+        0
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_unregister_flow_event_callback(callback: ffi::IUnityProfilerFlowEventCallback, user_data: *mut c_void) -> c_int {
+        with_state(|s| {
+            remove_callback(&mut s.flow_callbacks, callback.map(|f| f as usize), user_data as usize, |f| f as usize)
+        })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_get_renderer() -> ffi::UnityGfxRenderer {
+        with_state(|s| s.renderer)
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_register_device_event_callback(callback: ffi::IUnityGraphicsDeviceEventCallback) {
+        with_state(|s| s.device_callbacks.push(callback));
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_unregister_device_event_callback(callback: ffi::IUnityGraphicsDeviceEventCallback) {
+        with_state(|s| {
+            if let Some(pos) = s
+                .device_callbacks
+                .iter()
+                .position(|registered| registered.map(|f| f as usize) == callback.map(|f| f as usize))
+            {
+                s.device_callbacks.remove(pos);
+            }
+        });
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_reserve_event_id_range(count: c_int) -> c_int {
+        with_state(|s| {
+            let base = s.next_event_id;
+            s.next_event_id += count.max(0);
+            base
+        })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_create_allocator(area: *const c_char, object: *const c_char) -> *mut ffi::UnityAllocator {
+        let (area, object) = unsafe { (string_from_c(area), string_from_c(object)) };
+
+        with_state(|s| {
+            s.allocators.push(MockAllocator {
+                record: AllocatorRecord {
+                    area,
+                    object,
+                    destroyed: false,
+                    live_allocations: 0,
+                },
+                live: HashMap::new(),
+            });
+
+            // Allocator handles are opaque to plugins, so the 1-based index is enough
+            std::ptr::without_provenance_mut(s.allocators.len())
+        })
+    }
+}
+
+fn with_allocator<R>(
+    allocator: *mut ffi::UnityAllocator,
+    f: impl FnOnce(&mut MockAllocator) -> R,
+) -> Option<R> {
+    with_state(|s| {
+        (allocator.addr())
+            .checked_sub(1)
+            .and_then(|index| s.allocators.get_mut(index))
+            .filter(|a| !a.record.destroyed)
+            .map(f)
+    })
+}
+
+unity_api_fn! {
+    unsafe fn mock_destroy_allocator(allocator: *mut ffi::UnityAllocator) {
+        with_allocator(allocator, |a| a.record.destroyed = true);
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_allocate(
+        allocator: *mut ffi::UnityAllocator,
+        size: usize,
+        align: usize,
+        _file: *const c_char,
+        _line: i32,
+    ) -> *mut c_void {
+        let Ok(layout) = std::alloc::Layout::from_size_align(size.max(1), align.max(1)) else {
+            return null_mut();
+        };
+
+        with_allocator(allocator, |a| {
+            let ptr = unsafe { std::alloc::alloc(layout) };
+
+            if !ptr.is_null() {
+                a.live.insert(ptr as usize, layout);
+            }
+
+            ptr.cast()
+        })
+        .unwrap_or(null_mut())
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_deallocate(allocator: *mut ffi::UnityAllocator, ptr: *mut c_void, _file: *const c_char, _line: i32) {
+        with_allocator(allocator, |a| {
+            if let Some(layout) = a.live.remove(&(ptr as usize)) {
+                unsafe { std::alloc::dealloc(ptr.cast(), layout) };
+            }
+        });
+    }
+}
+
+unity_api_fn! {
+    unsafe fn mock_reallocate(
+        allocator: *mut ffi::UnityAllocator,
+        ptr: *mut c_void,
+        size: usize,
+        align: usize,
+        file: *const c_char,
+        line: i32,
+    ) -> *mut c_void {
+        if ptr.is_null() {
+            return unsafe { mock_allocate(allocator, size, align, file, line) };
+        }
+
+        with_allocator(allocator, |a| {
+            let Some(old_layout) = a.live.remove(&(ptr as usize)) else {
+                return null_mut();
+            };
+
+            let new_ptr = unsafe { std::alloc::realloc(ptr.cast(), old_layout, size.max(1)) };
+
+            if new_ptr.is_null() {
+                a.live.insert(ptr as usize, old_layout);
+            } else {
+                let layout = std::alloc::Layout::from_size_align(size.max(1), old_layout.align())
+                    .expect("Layout was valid before reallocation");
+                a.live.insert(new_ptr as usize, layout);
+            }
+
+            new_ptr.cast()
+        })
+        .unwrap_or(null_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn captures_logs() {
+        let mock = MockUnity::new();
+        let logger = mock
+            .interfaces()
+            .get::<crate::logger::UnityLogger>()
+            .unwrap();
+
+        logger.log_warning("careful\0now", "src/lib.rs", 12);
+
+        assert_eq!(
+            mock.logs(),
+            vec![LogRecord {
+                level: ffi::UnityLogType::kUnityLogTypeWarning,
+                message: "careful\u{FFFD}now".to_string(),
+                file: "src/lib.rs".to_string(),
+                line: 12,
+            }]
+        );
+    }
+
+    #[cfg(feature = "profiler")]
+    #[test]
+    fn records_markers_and_events() {
+        use crate::profiler::{
+            MarkerDataType, MarkerDataUnit, MarkerMeta, MarkerMetaData, MarkerMetaDescriptor,
+            UnityProfiler,
+        };
+
+        struct Meta(i32);
+
+        impl MarkerMeta<1> for Meta {
+            fn get_descriptors() -> [MarkerMetaDescriptor; 1] {
+                [MarkerMetaDescriptor::new(
+                    "count",
+                    MarkerDataType::Int32,
+                    MarkerDataUnit::Count,
+                )]
+            }
+
+            fn get_data(&self) -> [MarkerMetaData<'_>; 1] {
+                [MarkerMetaData::Int32(self.0)]
+            }
+        }
+
+        let mock = MockUnity::new();
+        let profiler = mock.interfaces().get::<UnityProfiler>().unwrap();
+        let marker = profiler.create_marker_with_data::<Meta, 1>("Work").unwrap();
+
+        {
+            let _sample = marker.sample_scope_with_meta(&profiler, &Meta(7));
+        }
+
+        let markers = mock.markers();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].name, "Work");
+        assert_eq!(markers[0].metadata[0].name, "count");
+
+        let events = mock.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::Begin);
+        assert_eq!(events[0].data[0].bytes, 7i32.to_ne_bytes());
+        assert_eq!(events[1].kind, EventKind::End);
+
+        mock.set_profiler_enabled(false);
+        mock.clear_records();
+        {
+            let _sample = marker.sample_scope_with_meta(&profiler, &Meta(7));
+        }
+        assert!(mock.events().is_empty());
+    }
+
+    #[cfg(feature = "profiler")]
+    #[test]
+    fn registers_threads() {
+        let mock = MockUnity::new();
+        let profiler = mock
+            .interfaces()
+            .get::<crate::profiler::UnityProfiler>()
+            .unwrap();
+
+        let id = profiler
+            .register_current_thread("Workers", "Worker 0")
+            .unwrap();
+        profiler.unregister_thread(id).unwrap();
+
+        let threads = mock.threads();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].group, "Workers");
+        assert!(!threads[0].registered);
+    }
+
+    #[test]
+    fn hidden_interfaces_are_null() {
+        let mock = MockUnity::new();
+        mock.hide_interface(unity_api_guid!(0x9E7507FA5B444D5D 0x92FB979515EA83FC));
+
+        let get = unsafe { (*mock.raw()).GetInterfaceSplit }.unwrap();
+        assert!(unsafe { get(LOG_GUID.0, LOG_GUID.1) }.is_null());
+        assert!(!unsafe { get(GRAPHICS_GUID.0, GRAPHICS_GUID.1) }.is_null());
+    }
+}
@@ -0,0 +1,148 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+#[cfg(feature = "log")]
+use std::sync::RwLock;
+
+#[cfg(feature = "log")]
+use crate::logger::UnityLogger;
+
+use crate::UnityInterfaces;
+
+/// The logger used to report panics, available between plugin load and unload
+#[cfg(feature = "log")]
+static PANIC_LOGGER: RwLock<Option<UnityLogger>> = RwLock::new(None);
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// The location of the last panic on this thread, recorded by the panic hook
+    static LAST_LOCATION: RefCell<Option<(String, u32)>> = const { RefCell::new(None) };
+}
+
+/// Chains a panic hook that records the panic location, because the payload
+/// caught by [panic::catch_unwind] no longer contains it
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            let location = info
+                .location()
+                .map(|location| (location.file().to_owned(), location.line()));
+
+            LAST_LOCATION.with(|last| *last.borrow_mut() = location);
+
+            previous(info);
+        }));
+    });
+}
+
+/// Stores the logger used for reporting panics, if Unity provides one
+pub(crate) fn set_logger(interfaces: &UnityInterfaces) {
+    #[cfg(feature = "log")]
+    {
+        *PANIC_LOGGER.write().unwrap_or_else(|e| e.into_inner()) = interfaces.get().ok();
+    }
+
+    #[cfg(not(feature = "log"))]
+    let _ = interfaces;
+}
+
+/// Drops the logger used for reporting panics, because it may not be used after unload
+pub(crate) fn clear_logger() {
+    #[cfg(feature = "log")]
+    {
+        *PANIC_LOGGER.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&'static str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.as_str()
+    } else {
+        "<non-string panic payload>"
+    }
+}
+
+fn report(entry: &str, payload: &(dyn Any + Send)) {
+    let message = format!("Rust panic in {}: {}", entry, payload_message(payload));
+    let location = LAST_LOCATION.with(|last| last.borrow_mut().take());
+
+    #[cfg(feature = "log")]
+    {
+        let logger = PANIC_LOGGER.read().unwrap_or_else(|e| e.into_inner());
+        let (file, line) = location.unwrap_or_else(|| ("<unknown file>".to_owned(), 0));
+
+        match logger.as_ref() {
+            Some(logger) => logger.log_exception(&message, &file, line),
+            None => log::error!("{message} ({file}:{line})"),
+        }
+    }
+
+    #[cfg(not(feature = "log"))]
+    let _ = (message, location);
+}
+
+/// Reports an error from the crate itself through the same logger as panics
+pub(crate) fn log_error(message: &str) {
+    #[cfg(feature = "log")]
+    {
+        let logger = PANIC_LOGGER.read().unwrap_or_else(|e| e.into_inner());
+
+        match logger.as_ref() {
+            Some(logger) => logger.log_error(message, file!(), line!()),
+            None => log::error!("{message}"),
+        }
+    }
+
+    #[cfg(not(feature = "log"))]
+    let _ = message;
+}
+
+/// Runs the given function, catching any panic so that it does not unwind into Unity.
+/// A caught panic is reported as an exception through the Unity logger when one
+/// is available, and otherwise through the `log` facade, after which `default` is returned.
+/// Without the `log` feature, panics outside of plugin load and unload are not reported.
+pub fn guard<R>(entry: &str, default: R, f: impl FnOnce() -> R) -> R {
+    install_hook();
+
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            // Reporting must never unwind either, for example due to a panicking logger
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| report(entry, payload.as_ref())));
+            default
+        }
+    }
+}
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn reports_panic_as_exception() {
+        let mock = MockUnity::new();
+        set_logger(&mock.interfaces());
+
+        let result = guard("test_entry", 1, || -> i32 { panic!("oh no") });
+        clear_logger();
+
+        assert_eq!(result, 1);
+
+        let logs = mock.logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].level,
+            crate::ffi::UnityLogType::kUnityLogTypeException
+        );
+        assert_eq!(logs[0].message, "Rust panic in test_entry: oh no");
+        assert!(logs[0].file.ends_with("panic.rs"));
+    }
+}
@@ -0,0 +1,272 @@
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::__private::ReserveRenderEvents;
+use crate::graphics::{
+    GfxDeviceEvent, RenderThread, RenderingExtEvent, UnityGraphics, drain_render_commands,
+    drive_gpu_resources, flush_render_commands, handle_custom_blit, handle_texture_update,
+};
+use crate::panic::guard;
+use crate::{RawUnityInterfaces, UnityInterfaces, ffi};
+
+/// The lifecycle of a native plugin. Implemented by the type annotated with
+/// [unity_plugin](crate::unity_plugin), which generates all exported entry points
+/// and keeps the plugin instance alive between load and unload.
+///
+/// Device events take a mutable reference, while render events may arrive
+/// concurrently with other calls and only take a shared reference.
+pub trait UnityPlugin: Send + Sync + 'static {
+    /// Called from `UnityPluginLoad`, constructs the plugin instance
+    fn on_load(interfaces: UnityInterfaces) -> Self
+    where
+        Self: Sized;
+
+    /// Called from `UnityPluginUnload`, right before the plugin instance is dropped
+    fn on_unload(&mut self) {}
+
+    /// Called for every graphics device event. Unity does not repeat the
+    /// [GfxDeviceEvent::Initialize] event for plugins loaded after the device
+    /// was created, so it is also sent once directly after [UnityPlugin::on_load].
+    fn on_gfx_device_event(&mut self, _event: GfxDeviceEvent) {}
+
+    /// Called on the render thread for events issued from C# with `GL.IssuePluginEvent`
+    /// or `CommandBuffer.IssuePluginEvent`, using the function from `GetRenderEventFunc`
+    fn on_render_event(&self, _render_thread: &RenderThread, _event_id: i32) {}
+
+    /// Called on the render thread through the exported `UnityRenderingExtEvent` hook,
+    /// with the event parameters decoded into a [RenderingExtEvent]
+    fn on_rendering_ext_event(&self, _render_thread: &RenderThread, _event: RenderingExtEvent) {}
+}
+
+static PLUGIN: RwLock<Option<Box<dyn UnityPlugin>>> = RwLock::new(None);
+
+fn plugin_read() -> RwLockReadGuard<'static, Option<Box<dyn UnityPlugin>>> {
+    PLUGIN.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn plugin_write() -> RwLockWriteGuard<'static, Option<Box<dyn UnityPlugin>>> {
+    PLUGIN.write().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn load<T: UnityPlugin>(
+    raw: *mut RawUnityInterfaces,
+    render_events: &[ReserveRenderEvents],
+) {
+    guard("UnityPluginLoad", (), || {
+        let interfaces = unsafe { UnityInterfaces::new(raw) }
+            .expect("Unity provided a NULL IUnityInterfaces pointer");
+
+        crate::__private::plugin_loaded(&interfaces);
+
+        for reserve in render_events {
+            if let Err(err) = reserve() {
+                crate::panic::log_error(&format!("Could not reserve render event IDs: {err}"));
+            }
+        }
+
+        let plugin = T::on_load(interfaces.clone());
+        *plugin_write() = Some(Box::new(plugin));
+
+        let Ok(graphics) = interfaces.get::<UnityGraphics>() else {
+            return;
+        };
+
+        if let Ok(callback) = graphics.on_device_event(on_device_event) {
+            callback.forget();
+            dispatch_device_event(GfxDeviceEvent::Initialize);
+        }
+    });
+}
+
+pub(crate) fn unload() {
+    guard("UnityPluginUnload", (), crate::__private::plugin_unloading);
+
+    guard("UnityPluginUnload", (), || {
+        // Taken out first, so that render events arriving during unload are ignored
+        let plugin = plugin_write().take();
+
+        if let Some(mut plugin) = plugin {
+            plugin.on_unload();
+        }
+    });
+
+    guard("UnityPluginUnload", (), crate::__private::plugin_unloaded);
+}
+
+pub(crate) fn render_event_func() -> ffi::UnityRenderingEvent {
+    Some(render_event_trampoline)
+}
+
+pub(crate) fn rendering_ext_event(event: ffi::UnityRenderingExtEventType, data: *mut c_void) {
+    guard("UnityRenderingExtEvent", (), || {
+        let render_thread = unsafe { RenderThread::new() };
+        let mut event = unsafe { RenderingExtEvent::from_raw(event, data) };
+
+        if handle_texture_update(&render_thread, &mut event)
+            || handle_custom_blit(&render_thread, &event)
+        {
+            return;
+        }
+
+        if let Some(plugin) = plugin_read().as_ref() {
+            plugin.on_rendering_ext_event(&render_thread, event);
+        }
+    });
+}
+
+/// Drives the crate-managed state along with the plugin. GPU resources are recreated
+/// before, and released after the plugin sees the event, so that the plugin can use them.
+fn on_device_event(event: GfxDeviceEvent) {
+    match event {
+        GfxDeviceEvent::Initialize | GfxDeviceEvent::AfterReset => {
+            drive_gpu_resources(event);
+            dispatch_device_event(event);
+        }
+        _ => {
+            flush_render_commands(event);
+            dispatch_device_event(event);
+            drive_gpu_resources(event);
+        }
+    }
+}
+
+fn dispatch_device_event(event: GfxDeviceEvent) {
+    if let Some(plugin) = plugin_write().as_mut() {
+        plugin.on_gfx_device_event(event);
+    }
+}
+
+unity_api_fn! {
+    unsafe fn render_event_trampoline(event_id: c_int) {
+        guard("render event", (), || {
+            let render_thread = unsafe { RenderThread::new() };
+
+            if drain_render_commands(&render_thread, event_id) {
+                return;
+            }
+
+            if let Some(plugin) = plugin_read().as_ref() {
+                plugin.on_render_event(&render_thread, event_id);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::graphics::RenderCommandQueue;
+    use crate::mock::MockUnity;
+
+    static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn record(event: String) {
+        EVENTS.lock().unwrap().push(event);
+    }
+
+    struct TestPlugin;
+
+    impl UnityPlugin for TestPlugin {
+        fn on_load(_interfaces: UnityInterfaces) -> Self {
+            record("load".to_owned());
+            TestPlugin
+        }
+
+        fn on_unload(&mut self) {
+            record("unload".to_owned());
+        }
+
+        fn on_gfx_device_event(&mut self, event: GfxDeviceEvent) {
+            record(format!("{event:?}"));
+        }
+
+        fn on_render_event(&self, _render_thread: &RenderThread, event_id: i32) {
+            record(format!("render {event_id}"));
+        }
+    }
+
+    #[test]
+    fn drives_plugin_lifecycle() {
+        let mock = MockUnity::new();
+
+        load::<TestPlugin>(mock.raw(), &[]);
+        assert_eq!(mock.device_event_callback_count(), 1);
+
+        mock.fire_device_event(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventBeforeReset);
+        unsafe { render_event_func().unwrap()(7) };
+
+        // Nothing reserved an event ID at load, because the queue was not used yet
+        let graphics = mock.interfaces().get::<UnityGraphics>().unwrap();
+        assert_eq!(graphics.reserve_event_ids(1).unwrap(), 0);
+
+        RenderCommandQueue::push(|_| record("command".to_owned())).unwrap();
+        let drain_event = RenderCommandQueue::event_id().unwrap();
+        assert_eq!(drain_event, 1);
+        unsafe { render_event_func().unwrap()(drain_event) };
+
+        unload();
+        assert_eq!(mock.device_event_callback_count(), 0);
+
+        // Ignored, because the plugin is gone
+        unsafe { render_event_func().unwrap()(8) };
+
+        assert_eq!(
+            *EVENTS.lock().unwrap(),
+            [
+                "load",
+                "Initialize",
+                "BeforeReset",
+                "render 7",
+                "command",
+                "unload"
+            ]
+        );
+    }
+
+    struct EmptyPlugin;
+
+    impl UnityPlugin for EmptyPlugin {
+        fn on_load(_interfaces: UnityInterfaces) -> Self {
+            EmptyPlugin
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "log", feature = "profiler"))]
+    fn wrappers_are_inert_after_unload() {
+        use crate::logger::UnityLogger;
+        use crate::profiler::{CreateMarkerErr, UnityProfiler};
+
+        let mock = MockUnity::new();
+        mock.set_profiler_enabled(true);
+
+        load::<EmptyPlugin>(mock.raw(), &[]);
+
+        let interfaces = mock.interfaces();
+        let logger = interfaces.get::<UnityLogger>().unwrap();
+        let profiler = interfaces.get_best::<UnityProfiler>().unwrap();
+        let marker = profiler.create_marker("kept").unwrap();
+        assert_eq!(marker.get_name().as_deref(), Some("kept"));
+        let sample = marker.sample_scope(&profiler);
+
+        unload();
+        mock.clear_records();
+
+        logger.log_info("dropped", file!(), line!());
+        drop(sample);
+        marker.single_timeless(&profiler);
+
+        assert!(mock.logs().is_empty());
+        assert!(mock.events().is_empty());
+        assert!(!profiler.is_enabled());
+        assert_eq!(marker.get_name(), None);
+        assert!(matches!(
+            profiler.create_marker("late"),
+            Err(CreateMarkerErr::Unloaded)
+        ));
+        assert!(interfaces.get::<UnityGraphics>().is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use crate::graphics::{GfxRenderer, UnityGraphics};
+use crate::{UnityInterfaces, ffi};
+
+/// A Unity interface declared in the bundled Unity headers
+#[derive(Debug)]
+pub struct KnownInterface {
+    /// The name of the interface struct in the Unity headers
+    pub name: &'static str,
+
+    /// The GUID the interface is registered under
+    pub guid: ffi::UnityInterfaceGUID,
+}
+
+macro_rules! known_interfaces {
+    ($($name:ident => $high:literal $low:literal),* $(,)?) => {
+        /// Every interface declared in the bundled Unity headers, as checked by [UnityInterfaces::probe]
+        pub const KNOWN_INTERFACES: &[KnownInterface] = &[
+            $(KnownInterface {
+                name: stringify!($name),
+                guid: unity_api_guid!($high $low),
+            }),*
+        ];
+    };
+}
+
+known_interfaces! {
+    IUnityLog => 0x9E7507FA5B444D5D 0x92FB979515EA83FC,
+    IUnityProfilerV2 => 0xB957E0189CB6A30B 0x83CE589AE85B9068,
+    IUnityProfiler => 0x2CE79ED8316A4833 0x87076B2013E1571F,
+    IUnityProfilerCallbacksV2 => 0x5DEB59E88F2D4571 0x81E8583069A5E33C,
+    IUnityProfilerCallbacks => 0x572FDB38CE3C4B1F 0xA6071A9A7C4F52D8,
+    IUnityMemoryManager => 0xBAF9E57C61A811EC 0xC5A7CC7861A811EC,
+    IUnityEventQueue => 0x9959C347F5AE374D 0x9BADE6FC8EF49E7F,
+    IUnityGraphics => 0x7CBA0A9CA4DDB544 0x8C5AD4926EB17B11,
+    IUnityGraphicsVulkanV2 => 0x329334C09DCA4787 0xB347DD92A0097FFC,
+    IUnityGraphicsVulkan => 0x95355348D4EF4E11 0x9789313DFCFFCC87,
+    IUnityGraphicsD3D11 => 0xAAB37EF87A87D748 0xBF76967F07EFB177,
+    IUnityGraphicsD3D12v7 => 0x4624B0DA41B64AAC 0x915AABCB9BC3F0D3,
+    IUnityGraphicsD3D12v6 => 0xA396DCE58CAC4D78 0xAFDD9B281F20B840,
+    IUnityGraphicsD3D12v5 => 0xF5C8D8A37D37BC42 0xB02DFE93B5064A27,
+    IUnityGraphicsD3D12v4 => 0x498FFCC13EC94006 0xB18F8B0FF67778C8,
+    IUnityGraphicsD3D12v3 => 0x57C3FAFE59E5E843 0xBF4F5998474BB600,
+    IUnityGraphicsD3D12v2 => 0xEC39D2F18446C745 0xB1A2626641D6B11F,
+    IUnityGraphicsD3D12 => 0xEF4CEC88A45F4C4C 0xBD295B6F2A38D9DE,
+    IUnityGraphicsMetalV2 => 0xF58857784FEF46EC 0x9DB7A8803B87DA3D,
+    IUnityGraphicsMetalV1 => 0x29F8F3D03833465E 0x92138551C15D823D,
+    IUnityGraphicsMetal => 0x992C8EAEA95811E5 0x9A62C4B5B9876117,
+}
+
+/// Whether a single [KnownInterface] was provided by Unity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterfaceStatus {
+    /// The name of the interface struct in the Unity headers
+    pub name: &'static str,
+
+    /// Whether Unity returned a non-NULL pointer for the interface
+    pub present: bool,
+}
+
+/// The state of the Unity profiler at the time of probing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfilerStatus {
+    pub available: bool,
+    pub enabled: bool,
+}
+
+/// A report of the interfaces provided by the running Unity build,
+/// created with [UnityInterfaces::probe]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeReport {
+    /// The status of every interface in [KNOWN_INTERFACES], in the same order
+    pub interfaces: Vec<InterfaceStatus>,
+
+    /// The active renderer, if the graphics interface is available
+    pub renderer: Option<GfxRenderer>,
+
+    /// The profiler state, if the profiler interface is available.
+    /// Always [None] when the `profiler` feature is disabled.
+    pub profiler: Option<ProfilerStatus>,
+}
+
+impl ProbeReport {
+    /// Whether Unity provided the interface with the given header name
+    pub fn has(&self, name: &str) -> bool {
+        self.interfaces
+            .iter()
+            .any(|status| status.name == name && status.present)
+    }
+
+    /// Writes the full report to the Unity log as a single info message
+    #[cfg(feature = "log")]
+    pub fn log(&self, logger: &crate::logger::UnityLogger) {
+        logger.log_info(&self.to_string(), file!(), line!());
+    }
+}
+
+impl Display for ProbeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Unity native interface report")?;
+
+        match self.renderer {
+            Some(renderer) => writeln!(f, "  renderer: {renderer}")?,
+            None => writeln!(f, "  renderer: unknown")?,
+        }
+
+        match self.profiler {
+            Some(profiler) => writeln!(
+                f,
+                "  profiler: available={} enabled={}",
+                profiler.available, profiler.enabled
+            )?,
+            None => writeln!(f, "  profiler: unknown")?,
+        }
+
+        for status in &self.interfaces {
+            let state = if status.present { "present" } else { "missing" };
+            writeln!(f, "  {}: {}", status.name, state)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl UnityInterfaces {
+    /// Checks which of the [KNOWN_INTERFACES] the running Unity build provides,
+    /// together with the active renderer and the profiler state.
+    /// Useful for diagnosing plugin issues on other machines.
+    pub fn probe(&self) -> ProbeReport {
+        let interfaces = KNOWN_INTERFACES
+            .iter()
+            .map(|known| InterfaceStatus {
+                name: known.name,
+                present: self.get_raw(&known.guid).is_some(),
+            })
+            .collect();
+
+        let renderer = self
+            .get::<UnityGraphics>()
+            .ok()
+            .map(|graphics| graphics.renderer());
+
+        ProbeReport {
+            interfaces,
+            renderer,
+            profiler: self.probe_profiler(),
+        }
+    }
+
+    #[cfg(feature = "profiler")]
+    fn probe_profiler(&self) -> Option<ProfilerStatus> {
+        let profiler = self.get_best::<crate::profiler::UnityProfiler>().ok()?;
+
+        Some(ProfilerStatus {
+            available: profiler.is_available(),
+            enabled: profiler.is_enabled(),
+        })
+    }
+
+    #[cfg(not(feature = "profiler"))]
+    fn probe_profiler(&self) -> Option<ProfilerStatus> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn reports_mocked_interfaces() {
+        let mock = MockUnity::new();
+        mock.set_renderer(ffi::UnityGfxRenderer::kUnityGfxRendererVulkan);
+        mock.hide_interface(unity_api_guid!(0xBAF9E57C61A811EC 0xC5A7CC7861A811EC));
+
+        let report = mock.interfaces().probe();
+
+        assert_eq!(report.interfaces.len(), KNOWN_INTERFACES.len());
+        assert!(report.has("IUnityLog"));
+        assert!(report.has("IUnityGraphics"));
+        assert!(!report.has("IUnityMemoryManager"));
+        assert!(!report.has("IUnityGraphicsD3D11"));
+        assert_eq!(report.renderer, Some(GfxRenderer::Vulkan));
+
+        let text = report.to_string();
+        assert!(text.contains("renderer: Vulkan"));
+        assert!(text.contains("IUnityMemoryManager: missing"));
+    }
+}
@@ -0,0 +1,392 @@
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use std::ptr::NonNull;
+
+use thiserror::Error;
+
+use crate::{MissingFunctionsError, UnityInterface, VersionedInterface, deregister, ffi, lease};
+
+type RegisterFrameFn =
+    unity_api_fn_type!(fn(ffi::IUnityProfilerFrameCallback, *mut c_void) -> c_int);
+type RegisterFlowFn =
+    unity_api_fn_type!(fn(ffi::IUnityProfilerFlowEventCallback, *mut c_void) -> c_int);
+
+type FrameClosure = Box<dyn Fn() + Send + Sync>;
+type FlowClosure = Box<dyn Fn(FlowEventType, u32) + Send + Sync>;
+
+/// The supported versions of the Unity profiler callbacks API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfilerCallbacksVersion {
+    /// `IUnityProfilerCallbacks`, available since Unity 2018.2
+    V1,
+
+    /// `IUnityProfilerCallbacksV2`, which adds flow events
+    V2,
+}
+
+/// A wrapper for the Unity profiler callbacks API, which notifies the plugin
+/// of profiler activity. Supports both [ProfilerCallbacksVersion::V1] and
+/// [ProfilerCallbacksVersion::V2], see [crate::UnityInterfaces::get_best].
+#[derive(Debug)]
+pub struct UnityProfilerCallbacks {
+    version: ProfilerCallbacksVersion,
+    register_frame: RegisterFrameFn,
+    unregister_frame: RegisterFrameFn,
+    flow: Option<(RegisterFlowFn, RegisterFlowFn)>,
+    generation: u64,
+}
+
+unsafe impl Send for UnityProfilerCallbacks {}
+unsafe impl Sync for UnityProfilerCallbacks {}
+
+/// An error while registering a profiler callback
+#[derive(Debug, Error)]
+pub enum ProfilerCallbackErr {
+    /// The bound version of the callbacks API does not support this callback
+    #[error("Callback is not supported by profiler callbacks {0:?}")]
+    Unsupported(ProfilerCallbacksVersion),
+
+    #[error("Unity API returned an error code: {}", .0)]
+    Unity(c_int),
+
+    #[error("The plugin was unloaded")]
+    Unloaded,
+}
+
+/// The type of a profiler flow event, which connects samples across threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowEventType {
+    Begin,
+    ParallelNext,
+    End,
+    Next,
+    Unknown(ffi::UnityProfilerFlowEventType),
+}
+
+impl From<ffi::UnityProfilerFlowEventType> for FlowEventType {
+    fn from(value: ffi::UnityProfilerFlowEventType) -> Self {
+        match value as std::os::raw::c_uint {
+            x if x == ffi::UnityProfilerFlowEventType_::kUnityProfilerFlowEventTypeBegin.0 => {
+                FlowEventType::Begin
+            }
+            x if x
+                == ffi::UnityProfilerFlowEventType_::kUnityProfilerFlowEventTypeParallelNext.0 =>
+            {
+                FlowEventType::ParallelNext
+            }
+            x if x == ffi::UnityProfilerFlowEventType_::kUnityProfilerFlowEventTypeEnd.0 => {
+                FlowEventType::End
+            }
+            x if x == ffi::UnityProfilerFlowEventType_::kUnityProfilerFlowEventTypeNext.0 => {
+                FlowEventType::Next
+            }
+            _ => FlowEventType::Unknown(value),
+        }
+    }
+}
+
+/// A registered profiler callback. The callback is unregistered when this is dropped,
+/// or when the plugin is unloaded, whichever comes first.
+#[must_use = "The callback is unregistered when the handle is dropped"]
+#[derive(Debug)]
+pub struct ProfilerCallback {
+    id: Option<deregister::TrackId>,
+}
+
+impl ProfilerCallback {
+    /// Keeps the callback registered for the rest of the plugin lifetime
+    pub fn forget(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for ProfilerCallback {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            // Unity might still call a callback it failed to unregister, which is why
+            // the closure is leaked in that case, so there is nothing more to do here
+            let _ = deregister::undo(id);
+        }
+    }
+}
+
+unsafe impl UnityInterface for UnityProfilerCallbacks {
+    type FFIType = ffi::IUnityProfilerCallbacksV2;
+    type FFIConversionError = MissingFunctionsError;
+    const GUID: ffi::UnityInterfaceGUID = unity_api_guid!(0x5DEB59E88F2D4571 0x81E8583069A5E33C);
+}
+
+impl TryFrom<NonNull<ffi::IUnityProfilerCallbacksV2>> for UnityProfilerCallbacks {
+    type Error = MissingFunctionsError;
+
+    fn try_from(value: NonNull<ffi::IUnityProfilerCallbacksV2>) -> Result<Self, Self::Error> {
+        let table = unsafe { value.as_ref() };
+
+        let (register_frame, unregister_frame) = require_fns!(
+            "UnityProfilerCallbacks",
+            table,
+            RegisterFrameCallback,
+            UnregisterFrameCallback
+        )?;
+
+        let flow = table
+            .RegisterFlowEventCallback
+            .zip(table.UnregisterFlowEventCallback);
+
+        Ok(Self {
+            version: ProfilerCallbacksVersion::V2,
+            register_frame,
+            unregister_frame,
+            flow,
+            generation: lease::current(),
+        })
+    }
+}
+
+impl TryFrom<NonNull<ffi::IUnityProfilerCallbacks>> for UnityProfilerCallbacks {
+    type Error = MissingFunctionsError;
+
+    fn try_from(value: NonNull<ffi::IUnityProfilerCallbacks>) -> Result<Self, Self::Error> {
+        let (register_frame, unregister_frame) = require_fns!(
+            "UnityProfilerCallbacks",
+            unsafe { value.as_ref() },
+            RegisterFrameCallback,
+            UnregisterFrameCallback
+        )?;
+
+        Ok(Self {
+            version: ProfilerCallbacksVersion::V1,
+            register_frame,
+            unregister_frame,
+            flow: None,
+            generation: lease::current(),
+        })
+    }
+}
+
+unsafe impl VersionedInterface for UnityProfilerCallbacks {
+    type Version = ProfilerCallbacksVersion;
+    type FFIConversionError = MissingFunctionsError;
+
+    const VERSIONS: &'static [(ProfilerCallbacksVersion, ffi::UnityInterfaceGUID)] = &[
+        (ProfilerCallbacksVersion::V2, <Self as UnityInterface>::GUID),
+        (
+            ProfilerCallbacksVersion::V1,
+            unity_api_guid!(0x572FDB38CE3C4B1F 0xA6071A9A7C4F52D8),
+        ),
+    ];
+
+    unsafe fn from_versioned_ptr(
+        version: ProfilerCallbacksVersion,
+        ptr: NonNull<c_void>,
+    ) -> Result<Self, MissingFunctionsError> {
+        match version {
+            ProfilerCallbacksVersion::V2 => {
+                Self::try_from(ptr.cast::<ffi::IUnityProfilerCallbacksV2>())
+            }
+            ProfilerCallbacksVersion::V1 => {
+                Self::try_from(ptr.cast::<ffi::IUnityProfilerCallbacks>())
+            }
+        }
+    }
+
+    fn version(&self) -> ProfilerCallbacksVersion {
+        self.version
+    }
+}
+
+impl UnityProfilerCallbacks {
+    /// Whether Unity provides the flow event callbacks, which are missing
+    /// from [ProfilerCallbacksVersion::V1]
+    pub fn supports_flow_events(&self) -> bool {
+        self.flow.is_some()
+    }
+
+    /// Registers a closure that is called on every profiler frame change
+    pub fn on_frame<F: Fn() + Send + Sync + 'static>(
+        &self,
+        callback: F,
+    ) -> Result<ProfilerCallback, ProfilerCallbackErr> {
+        let closure: FrameClosure = Box::new(callback);
+        let user_data = Box::into_raw(Box::new(closure)).cast::<c_void>();
+
+        let result = lease::with(self.generation, || unsafe {
+            (self.register_frame)(Some(frame_trampoline), user_data)
+        });
+
+        let Some(result) = result else {
+            drop(unsafe { Box::from_raw(user_data.cast::<FrameClosure>()) });
+            return Err(ProfilerCallbackErr::Unloaded);
+        };
+
+        if result != 0 {
+            drop(unsafe { Box::from_raw(user_data.cast::<FrameClosure>()) });
+            return Err(ProfilerCallbackErr::Unity(result));
+        }
+
+        let unregister_frame = self.unregister_frame;
+        let generation = self.generation;
+        let user_data = user_data as usize;
+
+        let id = deregister::track("profiler frame callback", move || unsafe {
+            let user_data = user_data as *mut c_void;
+
+            // Unity might still call a callback it failed to unregister, so leak it.
+            // After unload, Unity no longer calls into the plugin at all.
+            let result = lease::with(generation, || {
+                unregister_frame(Some(frame_trampoline), user_data)
+            })
+            .unwrap_or(0);
+
+            if result != 0 {
+                return Err(result);
+            }
+
+            drop(Box::from_raw(user_data.cast::<FrameClosure>()));
+            Ok(())
+        });
+
+        Ok(ProfilerCallback { id: Some(id) })
+    }
+
+    /// Registers a closure that is called for every profiler flow event.
+    /// Not supported by [ProfilerCallbacksVersion::V1].
+    pub fn on_flow_event<F: Fn(FlowEventType, u32) + Send + Sync + 'static>(
+        &self,
+        callback: F,
+    ) -> Result<ProfilerCallback, ProfilerCallbackErr> {
+        let (register_flow, unregister_flow) = self
+            .flow
+            .ok_or(ProfilerCallbackErr::Unsupported(self.version))?;
+
+        let closure: FlowClosure = Box::new(callback);
+        let user_data = Box::into_raw(Box::new(closure)).cast::<c_void>();
+
+        let result = lease::with(self.generation, || unsafe {
+            register_flow(Some(flow_trampoline), user_data)
+        });
+
+        let Some(result) = result else {
+            drop(unsafe { Box::from_raw(user_data.cast::<FlowClosure>()) });
+            return Err(ProfilerCallbackErr::Unloaded);
+        };
+
+        if result != 0 {
+            drop(unsafe { Box::from_raw(user_data.cast::<FlowClosure>()) });
+            return Err(ProfilerCallbackErr::Unity(result));
+        }
+
+        let generation = self.generation;
+        let user_data = user_data as usize;
+
+        let id = deregister::track("profiler flow event callback", move || unsafe {
+            let user_data = user_data as *mut c_void;
+
+            // See on_frame
+            let result = lease::with(generation, || {
+                unregister_flow(Some(flow_trampoline), user_data)
+            })
+            .unwrap_or(0);
+
+            if result != 0 {
+                return Err(result);
+            }
+
+            drop(Box::from_raw(user_data.cast::<FlowClosure>()));
+            Ok(())
+        });
+
+        Ok(ProfilerCallback { id: Some(id) })
+    }
+}
+
+unity_api_fn! {
+    unsafe fn frame_trampoline(user_data: *mut c_void) {
+        let closure = unsafe { &*user_data.cast::<FrameClosure>() };
+
+        crate::panic::guard("profiler frame callback", (), closure);
+    }
+}
+
+unity_api_fn! {
+    unsafe fn flow_trampoline(flow_event_type: ffi::UnityProfilerFlowEventType, flow_id: u32, user_data: *mut c_void) {
+        let closure = unsafe { &*user_data.cast::<FlowClosure>() };
+
+        crate::panic::guard("profiler flow event callback", (), || {
+            closure(flow_event_type.into(), flow_id)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn frame_callback_runs_until_dropped() {
+        let mock = MockUnity::new();
+        let callbacks = mock
+            .interfaces()
+            .get_best::<UnityProfilerCallbacks>()
+            .unwrap();
+
+        assert_eq!(callbacks.version(), ProfilerCallbacksVersion::V2);
+
+        let frames = Arc::new(AtomicUsize::new(0));
+        let counter = frames.clone();
+        let handle = callbacks
+            .on_frame(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+
+        mock.fire_profiler_frame();
+        assert_eq!(frames.load(Ordering::Relaxed), 1);
+
+        drop(handle);
+        assert_eq!(mock.profiler_callback_count(), 0);
+
+        mock.fire_profiler_frame();
+        assert_eq!(frames.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn forgotten_callback_is_unregistered_on_unload() {
+        let mock = MockUnity::new();
+        let callbacks = mock
+            .interfaces()
+            .get_best::<UnityProfilerCallbacks>()
+            .unwrap();
+
+        callbacks.on_frame(|| {}).unwrap().forget();
+        callbacks.on_flow_event(|_, _| {}).unwrap().forget();
+        assert_eq!(mock.profiler_callback_count(), 2);
+
+        crate::__private::plugin_unloading();
+        assert_eq!(mock.profiler_callback_count(), 0);
+    }
+
+    #[test]
+    fn flow_events_need_v2() {
+        let mock = MockUnity::new();
+        mock.hide_interface(<UnityProfilerCallbacks as UnityInterface>::GUID);
+
+        let callbacks = mock
+            .interfaces()
+            .get_best::<UnityProfilerCallbacks>()
+            .unwrap();
+
+        assert_eq!(callbacks.version(), ProfilerCallbacksVersion::V1);
+        assert!(!callbacks.supports_flow_events());
+        assert!(matches!(
+            callbacks.on_flow_event(|_, _| {}),
+            Err(ProfilerCallbackErr::Unsupported(
+                ProfilerCallbacksVersion::V1
+            ))
+        ));
+    }
+}
@@ -2,6 +2,7 @@ use std::ffi::CStr;
 use std::{ffi::CString, marker::PhantomData};
 
 use crate::ffi;
+use crate::lease;
 
 use super::sample::ManualProfilerSample;
 use super::sample::ScopedProfilerSample;
@@ -11,6 +12,7 @@ use super::UnityProfiler;
 #[derive(Debug)]
 pub struct ProfilerMarker<T: MarkerMeta<N> = (), const N: usize = 0> {
     desc_ptr: *const ffi::UnityProfilerMarkerDesc,
+    generation: u64,
     meta_type: PhantomData<T>,
 }
 
@@ -19,7 +21,7 @@ unsafe impl<T: MarkerMeta<N>, const N: usize> Sync for ProfilerMarker<T, N> {}
 
 pub trait MarkerMeta<const N: usize> {
     fn get_descriptors() -> [MarkerMetaDescriptor; N];
-    fn get_data(&self) -> [MarkerMetaData; N];
+    fn get_data(&self) -> [MarkerMetaData<'_>; N];
 }
 
 impl MarkerMeta<0> for () {
@@ -27,7 +29,7 @@ impl MarkerMeta<0> for () {
         []
     }
 
-    fn get_data(&self) -> [MarkerMetaData; 0] {
+    fn get_data(&self) -> [MarkerMetaData<'_>; 0] {
         []
     }
 }
@@ -192,9 +194,10 @@ impl ProfilerMarker<(), 0> {
 }
 
 impl<T: MarkerMeta<N>, const N: usize> ProfilerMarker<T, N> {
-    pub(super) unsafe fn new(raw_ptr: *const ffi::UnityProfilerMarkerDesc) -> Self {
+    pub(super) unsafe fn new(raw_ptr: *const ffi::UnityProfilerMarkerDesc, generation: u64) -> Self {
         Self {
             desc_ptr: raw_ptr,
+            generation,
             meta_type: PhantomData,
         }
     }
@@ -203,10 +206,22 @@ impl<T: MarkerMeta<N>, const N: usize> ProfilerMarker<T, N> {
         self.desc_ptr
     }
 
-    pub fn get_name(&self) -> &str {
-        let name_c = unsafe { CStr::from_ptr(self.desc_ptr.as_ref().unwrap().name) };
+    /// A copy of the name of the marker, with invalid UTF-8 replaced. The name is
+    /// owned by Unity, so this is [None] after the plugin was unloaded, or when
+    /// Unity did not provide a name.
+    pub fn get_name(&self) -> Option<String> {
+        lease::with(self.generation, || {
+            let desc = unsafe { self.desc_ptr.as_ref() }?;
 
-        name_c.to_str().unwrap()
+            if desc.name.is_null() {
+                return None;
+            }
+
+            let name_c = unsafe { CStr::from_ptr(desc.name) };
+
+            Some(name_c.to_string_lossy().into_owned())
+        })
+        .flatten()
     }
 
     pub fn sample_scope_with_meta<'a, 'b>(
@@ -1,26 +1,124 @@
+use std::ffi::CStr;
 use std::ffi::CString;
 use std::ffi::c_void;
-use std::os::raw::c_int;
+use std::os::raw::{c_char, c_int};
 use std::ptr::NonNull;
 use std::ptr::null;
 use std::ptr::null_mut;
 
 use thiserror::Error;
 
+use crate::MissingFunctionsError;
 use crate::UnityInterface;
+use crate::VersionedInterface;
 use crate::ffi;
-use crate::unity_api_guid;
+use crate::lease;
 
+mod callbacks;
 mod marker;
 mod sample;
 
+pub use callbacks::*;
 pub use marker::*;
 pub use sample::*;
 
+type EmitEventFn = unity_api_fn_type!(
+    fn(
+        *const ffi::UnityProfilerMarkerDesc,
+        ffi::UnityProfilerMarkerEventType,
+        u16,
+        *const ffi::UnityProfilerMarkerData,
+    )
+);
+type IsEnabledFn = unity_api_fn_type!(fn() -> c_int);
+type CreateMarkerFn = unity_api_fn_type!(
+    fn(
+        *mut *const ffi::UnityProfilerMarkerDesc,
+        *const c_char,
+        ffi::UnityProfilerCategoryId,
+        ffi::UnityProfilerMarkerFlags,
+        c_int,
+    ) -> c_int
+);
+type SetMarkerMetadataNameFn = unity_api_fn_type!(
+    fn(
+        *const ffi::UnityProfilerMarkerDesc,
+        c_int,
+        *const c_char,
+        ffi::UnityProfilerMarkerDataType,
+        ffi::UnityProfilerMarkerDataUnit,
+    ) -> c_int
+);
+type RegisterThreadFn =
+    unity_api_fn_type!(fn(*mut ffi::UnityProfilerThreadId, *const c_char, *const c_char) -> c_int);
+type UnregisterThreadFn = unity_api_fn_type!(fn(ffi::UnityProfilerThreadId) -> c_int);
+
+/// The profiler API functions used by [UnityProfiler], validated at construction
+#[derive(Debug, Clone, Copy)]
+struct ProfilerFns {
+    emit_event: EmitEventFn,
+    is_enabled: IsEnabledFn,
+    create_marker: CreateMarkerFn,
+    set_marker_metadata_name: SetMarkerMetadataNameFn,
+    register_thread: RegisterThreadFn,
+    unregister_thread: UnregisterThreadFn,
+}
+
+/// Takes the functions shared by all profiler versions out of the given table,
+/// returning them together with the `IsAvailable` function
+macro_rules! shared_profiler_fns {
+    ($table:expr) => {{
+        let (
+            is_available,
+            emit_event,
+            is_enabled,
+            create_marker,
+            set_marker_metadata_name,
+            register_thread,
+            unregister_thread,
+        ) = require_fns!(
+            "UnityProfiler",
+            $table,
+            IsAvailable,
+            EmitEvent,
+            IsEnabled,
+            CreateMarker,
+            SetMarkerMetadataName,
+            RegisterThread,
+            UnregisterThread,
+        )?;
+
+        let fns = ProfilerFns {
+            emit_event,
+            is_enabled,
+            create_marker,
+            set_marker_metadata_name,
+            register_thread,
+            unregister_thread,
+        };
+
+        (fns, is_available)
+    }};
+}
+
+/// The supported versions of the Unity profiler API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfilerVersion {
+    /// `IUnityProfiler`, available since Unity 2020.1
+    V1,
+
+    /// `IUnityProfilerV2`, which adds categories and counters
+    V2,
+}
+
 #[derive(Debug)]
 pub struct UnityProfiler {
-    ptr: NonNull<ffi::IUnityProfilerV2>,
+    fns: ProfilerFns,
+    generation: u64,
     available: bool,
+    version: ProfilerVersion,
+    categories: bool,
+    counters: bool,
 }
 
 unsafe impl Send for UnityProfiler {}
@@ -28,8 +126,8 @@ unsafe impl Sync for UnityProfiler {}
 
 #[derive(Debug, Error)]
 pub enum ProfilerCreationError {
-    #[error("Cannot check for profiler availability because the function pointer is missing")]
-    MissingAvailableFn,
+    #[error(transparent)]
+    MissingFunctions(#[from] MissingFunctionsError),
 }
 
 unsafe impl UnityInterface for UnityProfiler {
@@ -42,20 +140,64 @@ impl TryFrom<NonNull<ffi::IUnityProfilerV2>> for UnityProfiler {
     type Error = ProfilerCreationError;
 
     fn try_from(value: NonNull<ffi::IUnityProfilerV2>) -> Result<Self, Self::Error> {
-        let is_available = unsafe {
-            match value.as_ref().IsAvailable {
-                Some(func) => func() != 0,
-                None => return Err(ProfilerCreationError::MissingAvailableFn),
-            }
-        };
+        let table = unsafe { value.as_ref() };
+        let (fns, is_available) = shared_profiler_fns!(table);
 
         Ok(Self {
-            ptr: value,
-            available: is_available,
+            fns,
+            generation: lease::current(),
+            available: unsafe { is_available() != 0 },
+            version: ProfilerVersion::V2,
+            categories: table.CreateCategory.is_some(),
+            counters: table.CreateCounterValue.is_some() && table.FlushCounterValue.is_some(),
         })
     }
 }
 
+impl TryFrom<NonNull<ffi::IUnityProfiler>> for UnityProfiler {
+    type Error = ProfilerCreationError;
+
+    fn try_from(value: NonNull<ffi::IUnityProfiler>) -> Result<Self, Self::Error> {
+        let (fns, is_available) = shared_profiler_fns!(unsafe { value.as_ref() });
+
+        Ok(Self {
+            fns,
+            generation: lease::current(),
+            available: unsafe { is_available() != 0 },
+            version: ProfilerVersion::V1,
+            categories: false,
+            counters: false,
+        })
+    }
+}
+
+unsafe impl VersionedInterface for UnityProfiler {
+    type Version = ProfilerVersion;
+    type FFIConversionError = ProfilerCreationError;
+
+    const VERSIONS: &'static [(ProfilerVersion, ffi::UnityInterfaceGUID)] = &[
+        (ProfilerVersion::V2, <Self as UnityInterface>::GUID),
+        (
+            ProfilerVersion::V1,
+            unity_api_guid!(0x2CE79ED8316A4833 0x87076B2013E1571F),
+        ),
+    ];
+
+    unsafe fn from_versioned_ptr(
+        version: ProfilerVersion,
+        ptr: NonNull<c_void>,
+    ) -> Result<Self, ProfilerCreationError> {
+        match version {
+            ProfilerVersion::V2 => Self::try_from(ptr.cast::<ffi::IUnityProfilerV2>()),
+            ProfilerVersion::V1 => Self::try_from(ptr.cast::<ffi::IUnityProfiler>()),
+        }
+    }
+
+    fn version(&self) -> ProfilerVersion {
+        self.version
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum EventType {
     Begin,
@@ -86,19 +228,34 @@ pub enum CreateMarkerErr {
 
     #[error("Error returned by Unity during marker metadata creation: {0}")]
     MarkerMeta(std::os::raw::c_int),
+
+    #[error("The plugin was unloaded")]
+    Unloaded,
 }
 
 impl UnityProfiler {
+    /// Whether Unity provides `CreateCategory`, which is missing from [ProfilerVersion::V1]
+    pub fn supports_categories(&self) -> bool {
+        self.categories
+    }
+
+    /// Whether Unity provides `CreateCounterValue` and `FlushCounterValue`,
+    /// which are missing from [ProfilerVersion::V1]
+    pub fn supports_counters(&self) -> bool {
+        self.counters
+    }
+
+    /// Whether the profiler was available when this wrapper was created
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
     pub fn is_enabled(&self) -> bool {
         if !self.available {
             return false;
         }
 
-        let as_ref = unsafe { self.ptr.as_ref() };
-
-        let func = as_ref.IsEnabled.unwrap();
-
-        unsafe { func() != 0 }
+        lease::with(self.generation, || unsafe { (self.fns.is_enabled)() != 0 }).unwrap_or(false)
     }
 
     pub fn create_marker(&self, name: &str) -> Result<ProfilerMarker<(), 0>, CreateMarkerErr> {
@@ -121,15 +278,27 @@ impl UnityProfiler {
 
         let mut raw_marker: *const ffi::UnityProfilerMarkerDesc = null_mut();
 
-        unsafe {
-            let createfn = self.ptr.as_ref().CreateMarker.unwrap();
+        lease::with(self.generation, || {
+            self.create_marker_raw(&mut raw_marker, &name_c, &descriptors)
+        })
+        .unwrap_or(Err(CreateMarkerErr::Unloaded))?;
+
+        Ok(unsafe { ProfilerMarker::new(raw_marker, self.generation) })
+    }
 
-            let create_result = createfn(
-                &mut raw_marker,
+    fn create_marker_raw(
+        &self,
+        raw_marker: &mut *const ffi::UnityProfilerMarkerDesc,
+        name_c: &CStr,
+        descriptors: &[MarkerMetaDescriptor],
+    ) -> Result<(), CreateMarkerErr> {
+        unsafe {
+            let create_result = (self.fns.create_marker)(
+                raw_marker,
                 name_c.as_ptr(),
                 ffi::UnityBuiltinProfilerCategory_::kUnityProfilerCategoryOther.into(),
                 ffi::UnityProfilerMarkerFlag_::kUnityProfilerMarkerFlagDefault.into(),
-                N as c_int,
+                descriptors.len() as c_int,
             );
 
             if create_result != 0 {
@@ -139,10 +308,8 @@ impl UnityProfiler {
 
         for (i, descriptor) in descriptors.iter().enumerate() {
             let set_result = unsafe {
-                let setmetafn = self.ptr.as_ref().SetMarkerMetadataName.unwrap();
-
-                setmetafn(
-                    raw_marker,
+                (self.fns.set_marker_metadata_name)(
+                    *raw_marker,
                     i as i32,
                     descriptor.name_c().as_ptr(),
                     descriptor.datatype.into(),
@@ -155,7 +322,7 @@ impl UnityProfiler {
             }
         }
 
-        Ok(unsafe { ProfilerMarker::new(raw_marker) })
+        Ok(())
     }
 
     fn emit_event<T: MarkerMeta<N>, const N: usize>(
@@ -167,8 +334,9 @@ impl UnityProfiler {
         debug_assert!(self.available);
         debug_assert!(!marker.raw().is_null());
 
-        unsafe {
-            let emitfn = self.ptr.as_ref().EmitEvent.unwrap();
+        // Samples that end after the plugin was unloaded are dropped
+        lease::with(self.generation, || unsafe {
+            let emitfn = self.fns.emit_event;
 
             match meta {
                 None => emitfn(marker.raw(), event.into(), 0, null()),
@@ -198,7 +366,7 @@ impl UnityProfiler {
                     emitfn(marker.raw(), event.into(), N as u16, unity_edata_ptr);
                 }
             }
-        }
+        });
     }
 
     pub fn register_current_thread(
@@ -217,13 +385,14 @@ impl UnityProfiler {
         let group_name_c = CString::new(group_name).map_err(|_| RegisterThreadErr::Nul)?;
         let thread_name_c = CString::new(thread_name).map_err(|_| RegisterThreadErr::Nul)?;
 
-        let result = unsafe {
-            self.ptr.as_ref().RegisterThread.unwrap()(
+        let result = lease::with(self.generation, || unsafe {
+            (self.fns.register_thread)(
                 &raw mut out_thread_id,
                 group_name_c.as_ptr(),
                 thread_name_c.as_ptr(),
             )
-        };
+        })
+        .ok_or(RegisterThreadErr::Unloaded)?;
 
         match result {
             0 => Ok(UnityThreadId(out_thread_id)),
@@ -231,8 +400,13 @@ impl UnityProfiler {
         }
     }
 
+    /// Unregisters a profiler thread. Does nothing after the plugin was unloaded,
+    /// because Unity has forgotten about the thread by then.
     pub fn unregister_thread(&self, thread_id: UnityThreadId) -> Result<(), c_int> {
-        let result = unsafe { self.ptr.as_ref().UnregisterThread.unwrap()(thread_id.0) };
+        let result = lease::with(self.generation, || unsafe {
+            (self.fns.unregister_thread)(thread_id.0)
+        })
+        .unwrap_or(0);
 
         match result {
             0 => Ok(()),
@@ -258,4 +432,52 @@ pub enum RegisterThreadErr {
 
     #[error("Unity API returned an error code: {}", .0)]
     Unity(c_int),
+
+    #[error("The plugin was unloaded")]
+    Unloaded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[test]
+    fn falls_back_to_v1() {
+        let mock = MockUnity::new();
+
+        let profiler = mock.interfaces().get_best::<UnityProfiler>().unwrap();
+        assert_eq!(profiler.version(), ProfilerVersion::V2);
+        assert!(profiler.supports_categories());
+
+        mock.hide_interface(<UnityProfiler as UnityInterface>::GUID);
+
+        let profiler = mock.interfaces().get_best::<UnityProfiler>().unwrap();
+        assert_eq!(profiler.version(), ProfilerVersion::V1);
+        assert!(!profiler.supports_categories());
+        assert!(!profiler.supports_counters());
+        assert!(profiler.is_enabled());
+    }
+
+    #[test]
+    fn reports_all_missing_functions() {
+        let mut table = ffi::IUnityProfilerV2::default();
+
+        let err = UnityProfiler::try_from(NonNull::from(&mut table)).unwrap_err();
+
+        let ProfilerCreationError::MissingFunctions(missing) = err;
+        assert_eq!(missing.interface, "UnityProfiler");
+        assert_eq!(
+            missing.missing,
+            [
+                "IsAvailable",
+                "EmitEvent",
+                "IsEnabled",
+                "CreateMarker",
+                "SetMarkerMetadataName",
+                "RegisterThread",
+                "UnregisterThread"
+            ]
+        );
+    }
 }
@@ -44,7 +44,7 @@ impl<T: MarkerMeta<N>, const N: usize> ManualProfilerSample<'_, '_, T, N> {
             } => {
                 debug_assert!(
                     !(*ended),
-                    "Profiler sample of marker {} ended multiple times",
+                    "Profiler sample of marker {:?} ended multiple times",
                     marker.get_name()
                 );
                 *ended = true;
@@ -64,7 +64,7 @@ impl<T: MarkerMeta<N>, const N: usize> Drop for ManualProfilerSample<'_, '_, T,
         {
             debug_assert!(
                 *ended,
-                "Profiler sample of marker {} not ended",
+                "Profiler sample of marker {:?} not ended",
                 marker.get_name()
             );
         }
@@ -0,0 +1,121 @@
+use std::any::Any;
+use std::ptr::{NonNull, null_mut};
+use std::sync::Mutex;
+
+use crate::{PluginInterface, RegisterError, UnityInterfaces, ffi};
+
+/// An interface this plugin published, which has to stay alive until unload
+struct Registration {
+    guid: ffi::UnityInterfaceGUID,
+    interfaces: NonNull<ffi::IUnityInterfaces>,
+    _interface: Box<dyn Any + Send + Sync>,
+}
+
+// The interfaces pointer is only used to revoke the registration, which Unity allows from any thread
+unsafe impl Send for Registration {}
+
+static REGISTRATIONS: Mutex<Vec<Registration>> = Mutex::new(Vec::new());
+
+pub(crate) fn register<T: PluginInterface>(
+    interfaces: &UnityInterfaces,
+    interface: T,
+) -> Result<(), RegisterError> {
+    if size_of::<T>() == 0 {
+        return Err(RegisterError::ZeroSized);
+    }
+
+    let mut registrations = REGISTRATIONS.lock().unwrap_or_else(|e| e.into_inner());
+
+    let already_registered = registrations
+        .iter()
+        .any(|r| r.guid.m_GUIDHigh == T::GUID.m_GUIDHigh && r.guid.m_GUIDLow == T::GUID.m_GUIDLow);
+
+    if already_registered {
+        return Err(RegisterError::AlreadyRegistered);
+    }
+
+    let boxed = Box::new(interface);
+    let ptr = (&raw const *boxed)
+        .cast_mut()
+        .cast::<ffi::IUnityInterface>();
+
+    interfaces.register_raw(&T::GUID, ptr)?;
+
+    registrations.push(Registration {
+        guid: T::GUID,
+        interfaces: interfaces.ptr,
+        _interface: boxed,
+    });
+
+    Ok(())
+}
+
+/// Revokes all registrations from the Unity registry and frees the interfaces
+pub(crate) fn release_all() {
+    let registrations =
+        std::mem::take(&mut *REGISTRATIONS.lock().unwrap_or_else(|e| e.into_inner()));
+
+    for registration in registrations.into_iter().rev() {
+        let interfaces = UnityInterfaces::from_nonnull(registration.interfaces);
+
+        // Make sure no other plugin can fetch the interface after it is freed
+        let _ = interfaces.register_raw(&registration.guid, null_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUnity;
+
+    #[repr(C)]
+    struct Calculator {
+        add: extern "C" fn(i32, i32) -> i32,
+    }
+
+    unsafe impl PluginInterface for Calculator {
+        const GUID: ffi::UnityInterfaceGUID =
+            unity_api_guid!(0x0123456789ABCDEF 0xFEDCBA9876543210);
+    }
+
+    extern "C" fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    #[repr(C)]
+    struct Empty;
+
+    unsafe impl PluginInterface for Empty {
+        const GUID: ffi::UnityInterfaceGUID =
+            unity_api_guid!(0x0123456789ABCDEF 0x0123456789ABCDEF);
+    }
+
+    #[test]
+    fn publishes_until_unload() {
+        let mock = MockUnity::new();
+        let interfaces = mock.interfaces();
+
+        assert!(interfaces.get_plugin_interface::<Calculator>().is_none());
+
+        interfaces.register_interface(Calculator { add }).unwrap();
+
+        assert!(matches!(
+            interfaces.register_interface(Calculator { add }),
+            Err(RegisterError::AlreadyRegistered)
+        ));
+
+        let calculator = interfaces.get_plugin_interface::<Calculator>().unwrap();
+        assert_eq!((unsafe { calculator.as_ref() }.add)(2, 3), 5);
+
+        assert!(matches!(
+            interfaces.register_interface(Empty),
+            Err(RegisterError::ZeroSized)
+        ));
+        assert!(mock.registered_interface(Empty::GUID).is_none());
+
+        release_all();
+
+        assert!(mock.registered_interface(Calculator::GUID).is_none());
+        assert!(interfaces.get_plugin_interface::<Calculator>().is_none());
+    }
+}
@@ -0,0 +1,20 @@
+//! Checks that the generated plugin entry points use the calling convention
+//! of `UNITY_INTERFACE_API` on the target being compiled for. The entry points
+//! are coerced into the function pointer types that bindgen generates for that
+//! macro, so a mismatch fails to compile.
+
+use unity_native::{
+    RawUnityInterfaces, UnityInterfaces, unity_api_fn_type, unity_plugin_load, unity_plugin_unload,
+};
+
+#[unity_plugin_load]
+fn load(_interfaces: UnityInterfaces) {}
+
+#[unity_plugin_unload]
+fn unload() {}
+
+#[test]
+fn entry_points_use_unity_calling_convention() {
+    let _: unity_api_fn_type!(fn(*mut RawUnityInterfaces)) = UnityPluginLoad;
+    let _: unity_api_fn_type!(fn()) = UnityPluginUnload;
+}
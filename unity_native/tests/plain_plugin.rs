@@ -0,0 +1,20 @@
+//! Checks that `#[unity_plugin]` without rendering extension queries builds without the
+//! `GfxPlugin` name warning, so that plugins built with `-D warnings` are not affected.
+
+#![deny(deprecated)]
+
+use unity_native::{UnityInterfaces, UnityPlugin, unity_plugin};
+
+#[unity_plugin]
+struct PlainPlugin;
+
+impl UnityPlugin for PlainPlugin {
+    fn on_load(_interfaces: UnityInterfaces) -> Self {
+        PlainPlugin
+    }
+}
+
+#[test]
+fn exports_entry_points() {
+    assert!(GetRenderEventFunc().is_some());
+}
@@ -0,0 +1,64 @@
+//! Checks the entry points generated by `#[unity_plugin]`, see `entry_points.rs`
+//! for how their calling convention is checked.
+
+// The test binary is not named `GfxPlugin*`, which `rendering_ext_queries` warns about
+#![allow(deprecated)]
+
+use std::ffi::{c_char, c_int, c_void};
+
+use unity_native::graphics::{RenderThread, RenderingExtQuery};
+use unity_native::{
+    RawUnityInterfaces, RenderEvents, UnityInterfaces, UnityPlugin, ffi, unity_api_fn_type,
+    unity_plugin,
+};
+
+#[unity_plugin(
+    render_events(TestEvents),
+    rendering_ext_queries = RenderingExtQuery::OVERRIDE_VIEWPORT | RenderingExtQuery::OVERRIDE_SCISSOR
+)]
+struct TestPlugin;
+
+#[derive(RenderEvents)]
+#[render_events(handler = on_test_event)]
+enum TestEvents {
+    Only,
+}
+
+fn on_test_event(_render_thread: &RenderThread, _event: TestEvents) {}
+
+impl UnityPlugin for TestPlugin {
+    fn on_load(_interfaces: UnityInterfaces) -> Self {
+        TestPlugin
+    }
+}
+
+#[test]
+fn entry_points_use_unity_calling_convention() {
+    let _: unity_api_fn_type!(fn(*mut RawUnityInterfaces)) = UnityPluginLoad;
+    let _: unity_api_fn_type!(fn()) = UnityPluginUnload;
+    let _: unity_api_fn_type!(fn() -> ffi::UnityRenderingEvent) = GetRenderEventFunc;
+    let _: unity_api_fn_type!(fn() -> ffi::UnityRenderingEventAndData) = GetRenderEventAndDataFunc;
+    let _: unity_api_fn_type!(fn() -> c_int) = GetRenderCommandEventID;
+    let _: unity_api_fn_type!(fn() -> ffi::UnityRenderingEventAndData) = GetCustomBlitFunc;
+    let _: unity_api_fn_type!(fn(*const c_char) -> c_int) = GetCustomBlitCommandID;
+    let _: unity_api_fn_type!(fn(ffi::UnityRenderingExtEventType, *mut c_void)) =
+        UnityRenderingExtEvent;
+    let _: unity_api_fn_type!(fn(ffi::UnityRenderingExtQueryType) -> bool) = UnityRenderingExtQuery;
+    let _: unity_api_fn_type!(fn() -> ffi::UnityRenderingEvent) = GetTestEventsRenderEventFunc;
+    let _: unity_api_fn_type!(fn() -> c_int) = GetTestEventsBaseEventID;
+}
+
+#[test]
+fn render_event_func_is_not_null() {
+    assert!(GetRenderEventFunc().is_some());
+}
+
+#[test]
+fn rendering_ext_query_answers_declared_queries() {
+    assert!(UnityRenderingExtQuery(
+        ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverrideScissor
+    ));
+    assert!(!UnityRenderingExtQuery(
+        ffi::UnityRenderingExtQueryType::kUnityRenderingExtQueryOverridePresentFrame
+    ));
+}
@@ -0,0 +1,118 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident, LitInt, Path, Token, parenthesized, spanned::Spanned};
+
+/// The parsed contents of the `#[unity_interface(...)]` attribute
+struct InterfaceAttr {
+    guid: (LitInt, LitInt),
+    ffi: Path,
+    required: Vec<Ident>,
+}
+
+fn parse_attr(input: &DeriveInput) -> syn::Result<InterfaceAttr> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("unity_interface"))
+        .ok_or_else(|| {
+            syn::Error::new(
+                input.ident.span(),
+                "missing #[unity_interface(guid(..), ffi = ..)] attribute",
+            )
+        })?;
+
+    let mut guid = None;
+    let mut ffi = None;
+    let mut required = Vec::new();
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("guid") {
+            let content;
+            parenthesized!(content in meta.input);
+            let high: LitInt = content.parse()?;
+            content.parse::<Token![,]>()?;
+            let low: LitInt = content.parse()?;
+            guid = Some((high, low));
+            Ok(())
+        } else if meta.path.is_ident("ffi") {
+            ffi = Some(meta.value()?.parse::<Path>()?);
+            Ok(())
+        } else if meta.path.is_ident("required") {
+            meta.parse_nested_meta(|field| {
+                required.push(field.path.require_ident()?.clone());
+                Ok(())
+            })
+        } else {
+            Err(meta.error("expected `guid`, `ffi` or `required`"))
+        }
+    })?;
+
+    Ok(InterfaceAttr {
+        guid: guid.ok_or_else(|| syn::Error::new(attr.span(), "missing `guid(high, low)`"))?,
+        ffi: ffi.ok_or_else(|| syn::Error::new(attr.span(), "missing `ffi = <type>`"))?,
+        required,
+    })
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attr = parse_attr(&input)?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "UnityInterface can only be derived for structs",
+            ));
+        }
+    };
+
+    if fields.len() != 1 {
+        return Err(syn::Error::new(
+            fields.span(),
+            "UnityInterface wrappers must have exactly one field, holding the NonNull interface pointer",
+        ));
+    }
+
+    let construct = match fields {
+        Fields::Named(named) => {
+            let field = named.named.first().unwrap().ident.as_ref().unwrap();
+            quote! { Self { #field: value } }
+        }
+        Fields::Unnamed(_) => quote! { Self(value) },
+        Fields::Unit => unreachable!(),
+    };
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let ffi = &attr.ffi;
+    let (high, low) = &attr.guid;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let required = &attr.required;
+    let check = (!required.is_empty()).then(|| {
+        quote! {
+            unity_native::require_fns!(#name, unsafe { value.as_ref() }, #(#required),*)?;
+        }
+    });
+
+    let expanded = quote! {
+        unsafe impl #impl_generics unity_native::UnityInterface for #ident #ty_generics #where_clause {
+            type FFIType = #ffi;
+            type FFIConversionError = unity_native::MissingFunctionsError;
+            const GUID: unity_native::ffi::UnityInterfaceGUID = unity_native::unity_api_guid!(#high #low);
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<::core::ptr::NonNull<#ffi>> for #ident #ty_generics #where_clause {
+            type Error = unity_native::MissingFunctionsError;
+
+            fn try_from(value: ::core::ptr::NonNull<#ffi>) -> ::core::result::Result<Self, Self::Error> {
+                #check
+
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    };
+
+    Ok(TokenStream::from(expanded))
+}
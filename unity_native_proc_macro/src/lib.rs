@@ -1,6 +1,9 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{ItemFn, parse_macro_input};
+use syn::{DeriveInput, Expr, Item, ItemFn, Path, parse_macro_input, parse_quote};
+
+mod interface;
+mod render_events;
 
 #[proc_macro_attribute]
 pub fn unity_plugin_load(_: TokenStream, item: TokenStream) -> TokenStream {
@@ -8,18 +11,23 @@ pub fn unity_plugin_load(_: TokenStream, item: TokenStream) -> TokenStream {
 
     let fn_ident = input.sig.ident.clone();
 
-    let loader = quote! {
-        #input
+    let export = unity_api_export(parse_quote! {
+        fn UnityPluginLoad(interfaces: *mut unity_native::RawUnityInterfaces) {
+            unity_native::__private::guard("UnityPluginLoad", (), || {
+                let interfaces = unsafe { unity_native::UnityInterfaces::new(interfaces) }
+                    .expect("Unity provided a NULL IUnityInterfaces pointer");
 
-        #[unsafe(no_mangle)]
-        #[allow(non_snake_case)]
-        extern "stdcall" fn UnityPluginLoad(
-            interfaces: *mut unity_native::RawUnityInterfaces,
-        ) {
-            let interfaces = unsafe { unity_native::UnityInterfaces::new(interfaces).unwrap() };
+                unity_native::__private::plugin_loaded(&interfaces);
 
-            #fn_ident(interfaces);
+                #fn_ident(interfaces);
+            });
         }
+    });
+
+    let loader = quote! {
+        #input
+
+        #export
     };
 
     TokenStream::from(loader)
@@ -31,15 +39,266 @@ pub fn unity_plugin_unload(_: TokenStream, item: TokenStream) -> TokenStream {
 
     let fn_ident = input.sig.ident.clone();
 
+    let export = unity_api_export(parse_quote! {
+        fn UnityPluginUnload() {
+            unity_native::__private::guard(
+                "UnityPluginUnload",
+                (),
+                unity_native::__private::plugin_unloading,
+            );
+            unity_native::__private::guard("UnityPluginUnload", (), #fn_ident);
+            unity_native::__private::guard(
+                "UnityPluginUnload",
+                (),
+                unity_native::__private::plugin_unloaded,
+            );
+        }
+    });
+
     let loader = quote! {
         #input
 
-        #[unsafe(no_mangle)]
-        #[allow(non_snake_case)]
-        extern "stdcall" fn UnityPluginUnload() {
-            #fn_ident();
-        }
+        #export
     };
 
     TokenStream::from(loader)
 }
+
+/// Generates all exported plugin entry points for a type implementing `UnityPlugin`:
+/// `UnityPluginLoad`, `UnityPluginUnload`, `GetRenderEventFunc`, `GetRenderEventAndDataFunc`,
+/// `GetRenderCommandEventID`, `GetCustomBlitFunc`, `GetCustomBlitCommandID` and
+/// `UnityRenderingExtEvent`.
+/// The plugin instance is created on load and kept in a synchronised static until unload.
+///
+/// Types implementing `RenderEvents` can be listed as `#[unity_plugin(render_events(A, B))]`
+/// to reserve their event IDs at load, before `UnityPlugin::on_load` is called.
+///
+/// `#[unity_plugin(rendering_ext_queries = RenderingExtQuery::OVERRIDE_VIEWPORT)]` also
+/// exports `UnityRenderingExtQuery`, answering true for the given `RenderingExtQuery` flags.
+/// This marks the plugin as a rendering extension plugin. Unity only preloads those when
+/// their name starts with `GfxPlugin`, so this warns at build time when the crate name does not.
+///
+/// ```ignore
+/// #[unity_plugin]
+/// struct MyPlugin;
+///
+/// impl UnityPlugin for MyPlugin {
+///     fn on_load(interfaces: UnityInterfaces) -> Self {
+///         MyPlugin
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn unity_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as Item);
+
+    let mut render_events: Vec<Path> = Vec::new();
+    let mut rendering_ext_queries: Option<Expr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("render_events") {
+            meta.parse_nested_meta(|events| {
+                render_events.push(events.path);
+                Ok(())
+            })
+        } else if meta.path.is_ident("rendering_ext_queries") {
+            rendering_ext_queries = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `render_events` or `rendering_ext_queries`"))
+        }
+    });
+
+    parse_macro_input!(attr with parser);
+
+    let (ident, generics) = match &input {
+        Item::Struct(item) => (&item.ident, &item.generics),
+        Item::Enum(item) => (&item.ident, &item.generics),
+        other => {
+            return TokenStream::from(
+                syn::Error::new_spanned(
+                    other,
+                    "#[unity_plugin] can only be used on a struct or enum",
+                )
+                .into_compile_error(),
+            );
+        }
+    };
+
+    if !generics.params.is_empty() {
+        return TokenStream::from(
+            syn::Error::new_spanned(generics, "#[unity_plugin] types cannot be generic")
+                .into_compile_error(),
+        );
+    }
+
+    let rendering_ext_query = rendering_ext_queries.map(|queries| {
+        let name_warning = gfx_plugin_name_warning();
+        let export = unity_api_export(parse_quote! {
+            fn UnityRenderingExtQuery(query: unity_native::ffi::UnityRenderingExtQueryType) -> bool {
+                unity_native::__private::rendering_ext_query(#queries, query)
+            }
+        });
+
+        quote! {
+            #name_warning
+
+            #export
+        }
+    });
+
+    let exports = [
+        parse_quote! {
+            fn UnityPluginLoad(interfaces: *mut unity_native::RawUnityInterfaces) {
+                unity_native::__private::load_plugin::<#ident>(
+                    interfaces,
+                    &[#(<#render_events as unity_native::graphics::RenderEvents>::reserve),*],
+                );
+            }
+        },
+        parse_quote! {
+            fn UnityPluginUnload() {
+                unity_native::__private::unload_plugin();
+            }
+        },
+        parse_quote! {
+            fn GetRenderEventFunc() -> unity_native::ffi::UnityRenderingEvent {
+                unity_native::__private::render_event_func()
+            }
+        },
+        parse_quote! {
+            fn GetRenderCommandEventID() -> ::std::os::raw::c_int {
+                unity_native::__private::render_command_event_id()
+            }
+        },
+        parse_quote! {
+            fn GetRenderEventAndDataFunc() -> unity_native::ffi::UnityRenderingEventAndData {
+                unity_native::__private::render_event_and_data_func()
+            }
+        },
+        parse_quote! {
+            fn GetCustomBlitFunc() -> unity_native::ffi::UnityRenderingEventAndData {
+                unity_native::__private::custom_blit_func()
+            }
+        },
+        parse_quote! {
+            unsafe fn GetCustomBlitCommandID(
+                name: *const ::std::os::raw::c_char,
+            ) -> ::std::os::raw::c_int {
+                unsafe { unity_native::__private::custom_blit_command_id(name) }
+            }
+        },
+        parse_quote! {
+            fn UnityRenderingExtEvent(
+                event: unity_native::ffi::UnityRenderingExtEventType,
+                data: *mut ::core::ffi::c_void,
+            ) {
+                unity_native::__private::rendering_ext_event(event, data);
+            }
+        },
+    ]
+    .map(unity_api_export);
+
+    let plugin = quote! {
+        #input
+
+        #rendering_ext_query
+
+        #(#exports)*
+    };
+
+    TokenStream::from(plugin)
+}
+
+/// Exports a plugin entry point under its own name, with the calling convention that
+/// `UNITY_INTERFACE_API` has on the target: `stdcall` on 32-bit Windows and the C calling
+/// convention everywhere else. This matches the function pointer types bindgen generates.
+fn unity_api_export(function: ItemFn) -> impl quote::ToTokens {
+    let mut stdcall = function.clone();
+    stdcall.sig.abi = Some(parse_quote!(extern "stdcall"));
+
+    let mut c = function;
+    c.sig.abi = Some(parse_quote!(extern "C"));
+
+    quote! {
+        #[cfg(all(windows, target_arch = "x86"))]
+        #[unsafe(no_mangle)]
+        #[allow(non_snake_case)]
+        #stdcall
+
+        #[cfg(not(all(windows, target_arch = "x86")))]
+        #[unsafe(no_mangle)]
+        #[allow(non_snake_case)]
+        #c
+    }
+}
+
+/// Unity only preloads rendering extension plugins named `GfxPlugin*`, before the graphics
+/// device is created. Proc macros cannot emit warnings on stable, so this uses a deprecated item.
+fn gfx_plugin_name_warning() -> Option<impl quote::ToTokens> {
+    let crate_name = std::env::var("CARGO_CRATE_NAME").ok()?;
+
+    if crate_name.starts_with("GfxPlugin") {
+        return None;
+    }
+
+    let note = format!(
+        "the plugin library `{crate_name}` does not start with `GfxPlugin`, so Unity will not \
+         preload it for rendering extensions. Set `[lib] name = \"GfxPlugin...\"` in Cargo.toml"
+    );
+
+    Some(quote! {
+        const _: () = {
+            #[deprecated(note = #note)]
+            const GFX_PLUGIN_NAME_PREFIX: () = ();
+            GFX_PLUGIN_NAME_PREFIX
+        };
+    })
+}
+
+/// Implements `UnityInterface` for a wrapper struct around a raw Unity interface.
+/// The struct must have a single field, holding a `NonNull` pointer to the FFI type.
+///
+/// The `guid` and `ffi` keys are mandatory. Every function pointer listed in `required`
+/// is checked for NULL when the wrapper is constructed, and all missing ones are
+/// reported through a `MissingFunctionsError`.
+///
+/// ```ignore
+/// #[derive(UnityInterface)]
+/// #[unity_interface(
+///     guid(0x9E7507FA5B444D5D, 0x92FB979515EA83FC),
+///     ffi = unity_native::ffi::IUnityLog,
+///     required(Log)
+/// )]
+/// struct MyLogger {
+///     ptr: NonNull<unity_native::ffi::IUnityLog>,
+/// }
+/// ```
+#[proc_macro_derive(UnityInterface, attributes(unity_interface))]
+pub fn derive_unity_interface(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    interface::expand(input).unwrap_or_else(|err| TokenStream::from(err.into_compile_error()))
+}
+
+/// Implements `RenderEvents` for an enum with only unit variants, mapping every
+/// variant to an ID in a range reserved with Unity. The `handler` function is
+/// called on the render thread with the `RenderThread` token and the decoded event.
+///
+/// Also exports `Get<Enum>RenderEventFunc` and `Get<Enum>BaseEventID` for C#.
+///
+/// ```ignore
+/// #[derive(RenderEvents)]
+/// #[render_events(handler = on_render_event)]
+/// enum MyEvents {
+///     Upload,
+///     Draw,
+/// }
+///
+/// fn on_render_event(render_thread: &RenderThread, event: MyEvents) {}
+/// ```
+#[proc_macro_derive(RenderEvents, attributes(render_events))]
+pub fn derive_render_events(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    render_events::expand(input).unwrap_or_else(|err| TokenStream::from(err.into_compile_error()))
+}
@@ -0,0 +1,113 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Path, parse_quote};
+
+use crate::unity_api_export;
+
+fn parse_handler(input: &DeriveInput) -> syn::Result<Path> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("render_events"))
+        .ok_or_else(|| {
+            syn::Error::new(
+                input.ident.span(),
+                "missing #[render_events(handler = ..)] attribute",
+            )
+        })?;
+
+    let mut handler = None;
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("handler") {
+            handler = Some(meta.value()?.parse::<Path>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `handler`"))
+        }
+    })?;
+
+    handler.ok_or_else(|| syn::Error::new_spanned(attr, "missing `handler = <function>`"))
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let handler = parse_handler(&input)?;
+
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "RenderEvents can only be derived for enums",
+            ));
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "RenderEvents cannot be derived for generic enums",
+        ));
+    }
+
+    if let Some(variant) = variants.iter().find(|v| !matches!(v.fields, Fields::Unit)) {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "RenderEvents variants cannot have fields",
+        ));
+    }
+
+    let ident = &input.ident;
+    let count = variants.len() as i32;
+    let indices = (0..count).collect::<Vec<_>>();
+    let variant_idents = variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+
+    let func_export = format_ident!("Get{}RenderEventFunc", ident);
+    let base_export = format_ident!("Get{}BaseEventID", ident);
+
+    let func_export_fn = unity_api_export(parse_quote! {
+        fn #func_export() -> unity_native::ffi::UnityRenderingEvent {
+            unity_native::__private::render_events_func::<#ident>()
+        }
+    });
+    let base_export_fn = unity_api_export(parse_quote! {
+        fn #base_export() -> ::std::os::raw::c_int {
+            unity_native::__private::render_events_base_id::<#ident>()
+        }
+    });
+
+    let expanded = quote! {
+        impl unity_native::graphics::RenderEvents for #ident {
+            const COUNT: ::std::os::raw::c_int = #count;
+
+            fn range() -> &'static unity_native::graphics::RenderEventRange {
+                static RANGE: unity_native::graphics::RenderEventRange =
+                    unity_native::graphics::RenderEventRange::new();
+                &RANGE
+            }
+
+            fn to_index(&self) -> ::std::os::raw::c_int {
+                match self {
+                    #(#ident::#variant_idents => #indices,)*
+                }
+            }
+
+            fn from_index(index: ::std::os::raw::c_int) -> ::core::option::Option<Self> {
+                match index {
+                    #(#indices => ::core::option::Option::Some(#ident::#variant_idents),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn handle(self, render_thread: &unity_native::graphics::RenderThread) {
+                #handler(render_thread, self)
+            }
+        }
+
+        #func_export_fn
+
+        #base_export_fn
+    };
+
+    Ok(TokenStream::from(expanded))
+}
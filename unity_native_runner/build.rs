@@ -0,0 +1,7 @@
+fn main() {
+    // The session tests build the fixture plugin for the same target as the runner,
+    // because a plugin built for another architecture cannot be loaded
+    let target = std::env::var("TARGET").unwrap();
+    println!("cargo:rustc-env=TARGET={target}");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,73 @@
+//! A headless fake Unity host for integration testing native plugins.
+//!
+//! Loads a plugin library, passes it a simulated interface registry through
+//! `UnityPluginLoad` and then drives the session described by a script file.
+//! Every log call and profiler event made by the plugin is written to the
+//! output, so that runs can be diffed against a known-good transcript.
+//!
+//! Usage: `unity_native_runner <plugin library> <script> [output file]`
+//!
+//! Script commands, one per line, with `#` starting a comment:
+//!
+//! - `load` and `unload` call `UnityPluginLoad` and `UnityPluginUnload`
+//! - `frame [count]` advances one or more frames, firing the profiler frame callbacks for each
+//! - `renderer <null|d3d11|d3d12|gles3|glcore|metal|vulkan>` sets the active renderer
+//! - `profiler <on|off>` enables or disables the profiler
+//! - `device <initialize|shutdown|before_reset|after_reset>` fires a graphics device event
+//! - `render_event <id>` calls the callback returned by the plugin's `GetRenderEventFunc`
+
+mod script;
+mod session;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use session::Session;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if !(2..=3).contains(&args.len()) {
+        eprintln!("Usage: unity_native_runner <plugin library> <script> [output file]");
+        return ExitCode::FAILURE;
+    }
+
+    match run(
+        PathBuf::from(&args[0]),
+        PathBuf::from(&args[1]),
+        args.get(2).map(PathBuf::from),
+    ) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(
+    plugin: PathBuf,
+    script: PathBuf,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let script = std::fs::read_to_string(&script)
+        .map_err(|e| format!("Could not read script {}: {e}", script.display()))?;
+    let commands = script::parse(&script)?;
+
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    let mut session = Session::open(&plugin, out)?;
+
+    for command in commands {
+        session.run(command)?;
+    }
+
+    session.finish()?.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+use unity_native::ffi;
+
+/// A single step of a scripted session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Calls `UnityPluginLoad` with the simulated interface registry
+    Load,
+
+    /// Calls `UnityPluginUnload`
+    Unload,
+
+    /// Advances the given number of frames, firing the profiler frame callbacks for each
+    Frame(u32),
+
+    /// Sets the renderer reported by `IUnityGraphics::GetRenderer`
+    Renderer(ffi::UnityGfxRenderer),
+
+    /// Enables or disables the profiler
+    Profiler(bool),
+
+    /// Fires a graphics device event at all registered callbacks
+    Device(ffi::UnityGfxDeviceEventType),
+
+    /// Invokes the plugin's render event callback with the given event ID
+    RenderEvent(i32),
+}
+
+/// An error while parsing a session script
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Line {line}: {message}")]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses a session script. Every non-empty line holds a single command,
+/// and everything after a `#` is a comment.
+pub fn parse(script: &str) -> Result<Vec<Command>, ScriptError> {
+    let mut commands = Vec::new();
+
+    for (index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let command = parse_line(line).map_err(|message| ScriptError {
+            line: index + 1,
+            message,
+        })?;
+
+        commands.push(command);
+    }
+
+    Ok(commands)
+}
+
+fn parse_line(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let keyword = words.next().unwrap_or("");
+    let arg = words.next();
+
+    if let Some(extra) = words.next() {
+        return Err(format!("Unexpected argument '{extra}'"));
+    }
+
+    let command = match (keyword, arg) {
+        ("load", None) => Command::Load,
+        ("unload", None) => Command::Unload,
+        ("frame", None) => Command::Frame(1),
+        ("frame", Some(count)) => Command::Frame(parse_number(count)?),
+        ("renderer", Some(name)) => Command::Renderer(parse_renderer(name)?),
+        ("profiler", Some("on")) => Command::Profiler(true),
+        ("profiler", Some("off")) => Command::Profiler(false),
+        ("device", Some(event)) => Command::Device(parse_device_event(event)?),
+        ("render_event", Some(id)) => Command::RenderEvent(parse_number(id)?),
+        (keyword, _) => return Err(format!("Invalid command '{keyword}'")),
+    };
+
+    Ok(command)
+}
+
+fn parse_number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("Invalid number '{word}'"))
+}
+
+fn parse_renderer(name: &str) -> Result<ffi::UnityGfxRenderer, String> {
+    let renderer = match name {
+        "null" => ffi::UnityGfxRenderer::kUnityGfxRendererNull,
+        "d3d11" => ffi::UnityGfxRenderer::kUnityGfxRendererD3D11,
+        "d3d12" => ffi::UnityGfxRenderer::kUnityGfxRendererD3D12,
+        "gles3" => ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLES30,
+        "glcore" => ffi::UnityGfxRenderer::kUnityGfxRendererOpenGLCore,
+        "metal" => ffi::UnityGfxRenderer::kUnityGfxRendererMetal,
+        "vulkan" => ffi::UnityGfxRenderer::kUnityGfxRendererVulkan,
+        other => return Err(format!("Unknown renderer '{other}'")),
+    };
+
+    Ok(renderer)
+}
+
+fn parse_device_event(name: &str) -> Result<ffi::UnityGfxDeviceEventType, String> {
+    let event = match name {
+        "initialize" => ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventInitialize,
+        "shutdown" => ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventShutdown,
+        "before_reset" => ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventBeforeReset,
+        "after_reset" => ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventAfterReset,
+        other => return Err(format!("Unknown device event '{other}'")),
+    };
+
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_session() {
+        let script = "
+            # Boot the plugin
+            renderer vulkan
+            load
+            device initialize
+            frame 3   # a few idle frames
+            render_event 7
+            profiler off
+            device shutdown
+            unload
+        ";
+
+        assert_eq!(
+            parse(script).unwrap(),
+            vec![
+                Command::Renderer(ffi::UnityGfxRenderer::kUnityGfxRendererVulkan),
+                Command::Load,
+                Command::Device(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventInitialize),
+                Command::Frame(3),
+                Command::RenderEvent(7),
+                Command::Profiler(false),
+                Command::Device(ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventShutdown),
+                Command::Unload,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_line_of_error() {
+        let err = parse("load\n\nframe many\n").unwrap_err();
+
+        assert_eq!(err.line, 3);
+        assert_eq!(err.message, "Invalid number 'many'");
+    }
+
+    #[test]
+    fn rejects_extra_arguments() {
+        assert!(parse("unload now").is_err());
+        assert!(parse("device initialize twice").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::io::Write;
+use std::path::Path;
+
+use libloading::Library;
+use thiserror::Error;
+use unity_native::mock::{EventKind, MockUnity};
+use unity_native::{ffi, unity_api_fn_type};
+
+use crate::script::Command;
+
+type PluginLoadFn = unity_api_fn_type!(fn(*mut unity_native::RawUnityInterfaces));
+type PluginUnloadFn = unity_api_fn_type!(fn());
+type GetRenderEventFuncFn = unity_api_fn_type!(fn() -> ffi::UnityRenderingEvent);
+
+/// An error while driving a scripted session
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Could not open plugin library: {0}")]
+    Library(#[source] libloading::Error),
+
+    #[error("Plugin does not export {0}")]
+    MissingSymbol(&'static str),
+
+    #[error("Plugin returned a NULL render event callback")]
+    NullRenderEventFunc,
+
+    #[error("Plugin is already loaded")]
+    AlreadyLoaded,
+
+    #[error("Plugin is not loaded")]
+    NotLoaded,
+
+    #[error("Could not write session output: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A fake Unity session around a single plugin library. Every log call and
+/// profiler event the plugin makes is written to the output, grouped under
+/// the script command that caused it.
+pub struct Session<W: Write> {
+    mock: MockUnity,
+    library: Library,
+    loaded: bool,
+    frame: u64,
+    seen_markers: usize,
+    seen_threads: usize,
+    out: W,
+}
+
+impl<W: Write> Session<W> {
+    /// Opens the plugin library without loading the plugin yet
+    pub fn open(plugin: &Path, out: W) -> Result<Self, SessionError> {
+        let library = unsafe { Library::new(plugin) }.map_err(SessionError::Library)?;
+
+        Ok(Self {
+            mock: MockUnity::new(),
+            library,
+            loaded: false,
+            frame: 0,
+            seen_markers: 0,
+            seen_threads: 0,
+            out,
+        })
+    }
+
+    /// Runs a single script command and records its effects
+    pub fn run(&mut self, command: Command) -> Result<(), SessionError> {
+        match command {
+            Command::Load => self.load()?,
+            Command::Unload => self.unload()?,
+            Command::Frame(count) => {
+                for _ in 0..count {
+                    self.frame += 1;
+                    writeln!(self.out, "> frame {}", self.frame)?;
+
+                    // Flushed per frame, so that records are grouped under their own frame
+                    self.mock.fire_profiler_frame();
+                    self.flush_records()?;
+                }
+            }
+            Command::Renderer(renderer) => {
+                writeln!(self.out, "> renderer {}", renderer.0)?;
+                self.mock.set_renderer(renderer);
+            }
+            Command::Profiler(enabled) => {
+                writeln!(
+                    self.out,
+                    "> profiler {}",
+                    if enabled { "on" } else { "off" }
+                )?;
+                self.mock.set_profiler_enabled(enabled);
+            }
+            Command::Device(event) => {
+                writeln!(self.out, "> device {}", device_event_name(event))?;
+                self.require_loaded()?;
+                self.mock.fire_device_event(event);
+            }
+            Command::RenderEvent(id) => {
+                writeln!(self.out, "> render_event {id}")?;
+                self.require_loaded()?;
+
+                let get_func = self.symbol::<GetRenderEventFuncFn>("GetRenderEventFunc")?;
+                let callback = unsafe { get_func() }.ok_or(SessionError::NullRenderEventFunc)?;
+
+                unsafe { callback(id) };
+            }
+        }
+
+        self.flush_records()
+    }
+
+    /// Unloads the plugin if the script didn't, so that it is never left loaded
+    pub fn finish(mut self) -> Result<W, SessionError> {
+        if self.loaded {
+            writeln!(self.out, "# script ended without unloading")?;
+            self.unload()?;
+            self.flush_records()?;
+        }
+
+        Ok(self.out)
+    }
+
+    fn load(&mut self) -> Result<(), SessionError> {
+        writeln!(self.out, "> load")?;
+
+        if self.loaded {
+            return Err(SessionError::AlreadyLoaded);
+        }
+
+        let load = self.symbol::<PluginLoadFn>("UnityPluginLoad")?;
+        unsafe { load(self.mock.raw()) };
+        self.loaded = true;
+
+        Ok(())
+    }
+
+    fn unload(&mut self) -> Result<(), SessionError> {
+        writeln!(self.out, "> unload")?;
+        self.require_loaded()?;
+
+        let unload = self.symbol::<PluginUnloadFn>("UnityPluginUnload")?;
+        unsafe { unload() };
+        self.loaded = false;
+
+        let leftover = self.mock.device_event_callback_count();
+        if leftover != 0 {
+            writeln!(
+                self.out,
+                "warning: {leftover} graphics device callback(s) still registered after unload"
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn require_loaded(&self) -> Result<(), SessionError> {
+        if self.loaded {
+            Ok(())
+        } else {
+            Err(SessionError::NotLoaded)
+        }
+    }
+
+    fn symbol<T: Copy>(&self, name: &'static str) -> Result<T, SessionError> {
+        unsafe { self.library.get::<T>(name.as_bytes()) }
+            .map(|symbol| *symbol)
+            .map_err(|_| SessionError::MissingSymbol(name))
+    }
+
+    fn flush_records(&mut self) -> Result<(), SessionError> {
+        let markers = self.mock.markers();
+        for marker in &markers[self.seen_markers..] {
+            let metadata: Vec<_> = marker.metadata.iter().map(|m| m.name.as_str()).collect();
+            writeln!(
+                self.out,
+                "marker \"{}\" category={} metadata=[{}]",
+                marker.name,
+                marker.category,
+                metadata.join(", ")
+            )?;
+        }
+        self.seen_markers = markers.len();
+
+        let threads = self.mock.threads();
+        for thread in &threads[self.seen_threads..] {
+            writeln!(self.out, "thread {}/{}", thread.group, thread.name)?;
+        }
+        self.seen_threads = threads.len();
+
+        for log in self.mock.logs() {
+            writeln!(
+                self.out,
+                "log {} {}:{} {}",
+                log_level_name(log.level),
+                log.file,
+                log.line,
+                log.message
+            )?;
+        }
+
+        for event in self.mock.events() {
+            let kind = match event.kind {
+                EventKind::Begin => "begin".to_string(),
+                EventKind::End => "end".to_string(),
+                EventKind::Single => "single".to_string(),
+                EventKind::Other(other) => format!("type{other}"),
+            };
+
+            let data: Vec<_> = event
+                .data
+                .iter()
+                .map(|d| {
+                    let bytes: String = d.bytes.iter().map(|b| format!("{b:02x}")).collect();
+                    format!("{}:{}", d.datatype, bytes)
+                })
+                .collect();
+
+            writeln!(
+                self.out,
+                "event {} \"{}\" [{}]",
+                kind,
+                event.marker,
+                data.join(", ")
+            )?;
+        }
+
+        self.mock.clear_records();
+
+        Ok(())
+    }
+}
+
+fn device_event_name(event: ffi::UnityGfxDeviceEventType) -> &'static str {
+    match event {
+        ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventInitialize => "initialize",
+        ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventShutdown => "shutdown",
+        ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventBeforeReset => "before_reset",
+        ffi::UnityGfxDeviceEventType::kUnityGfxDeviceEventAfterReset => "after_reset",
+        _ => "unknown",
+    }
+}
+
+fn log_level_name(level: ffi::UnityLogType) -> &'static str {
+    match level {
+        ffi::UnityLogType::kUnityLogTypeLog => "info",
+        ffi::UnityLogType::kUnityLogTypeWarning => "warning",
+        ffi::UnityLogType::kUnityLogTypeError => "error",
+        ffi::UnityLogType::kUnityLogTypeException => "exception",
+        _ => "unknown",
+    }
+}
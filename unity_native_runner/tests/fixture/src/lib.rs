@@ -0,0 +1,29 @@
+//! A minimal plugin for the runner integration tests. Logs on load and unload,
+//! and on every profiler frame.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use unity_native::logger::UnityLogger;
+use unity_native::profiler::UnityProfilerCallbacks;
+use unity_native::{UnityInterfaces, unity_plugin_load, unity_plugin_unload};
+
+static FRAMES: AtomicU32 = AtomicU32::new(0);
+
+#[unity_plugin_load]
+fn load(interfaces: UnityInterfaces) {
+    let logger = interfaces.get::<UnityLogger>().unwrap();
+    logger.log_info("loaded", file!(), line!());
+
+    let callbacks = interfaces.get_best::<UnityProfilerCallbacks>().unwrap();
+
+    callbacks
+        .on_frame(move || {
+            let frame = FRAMES.fetch_add(1, Ordering::Relaxed) + 1;
+            logger.log_info(&format!("frame {frame}"), file!(), line!());
+        })
+        .unwrap()
+        .forget();
+}
+
+#[unity_plugin_unload]
+fn unload() {}
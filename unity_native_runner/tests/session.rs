@@ -0,0 +1,77 @@
+//! Runs scripted sessions against the plugin library in `tests/fixture`
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Builds the fixture plugin for the target of the runner into its own target directory,
+/// so that the nested build does not wait on the lock of the build running this test
+fn build_fixture() -> PathBuf {
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fixture");
+
+    let status = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--quiet",
+            "--package",
+            "runner_fixture",
+            "--target",
+            env!("TARGET"),
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .expect("Could not run cargo");
+
+    assert!(status.success(), "Could not build the fixture plugin");
+
+    target_dir
+        .join(env!("TARGET"))
+        .join("debug")
+        .join(format!("{DLL_PREFIX}runner_fixture{DLL_SUFFIX}"))
+}
+
+#[test]
+fn drives_frames_through_plugin() {
+    let plugin = build_fixture();
+    let script = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("frames.script");
+
+    std::fs::write(&script, "load\nframe 2\nunload\nframe\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_unity_native_runner"))
+        .arg(&plugin)
+        .arg(&script)
+        .output()
+        .expect("Could not run the runner");
+
+    assert!(
+        output.status.success(),
+        "Runner failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let transcript = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = transcript
+        .lines()
+        .filter(|line| line.starts_with('>') || line.starts_with("log"))
+        // Strips the level and source location from logs
+        .map(|line| match line.strip_prefix("log info ") {
+            Some(log) => log.split_once(' ').map_or(log, |(_, message)| message),
+            None => line,
+        })
+        .collect();
+
+    assert_eq!(
+        lines,
+        [
+            "> load",
+            "loaded",
+            "> frame 1",
+            "frame 1",
+            "> frame 2",
+            "frame 2",
+            "> unload",
+            "> frame 3",
+        ]
+    );
+}